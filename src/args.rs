@@ -1,6 +1,8 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr, time::Duration};
 
-use clap::Parser;
+use clap::{CommandFactory, ErrorKind, Parser};
+
+use crate::filter::Filter;
 
 /// Command line arguments.
 ///
@@ -13,8 +15,9 @@ use clap::Parser;
 /// the main use cases should work exactly like with the built-in harness.
 #[derive(Parser, Debug, Clone, Default)]
 #[clap(
-    help_template = "USAGE: [OPTIONS] [FILTER]\n\n{all-args}\n\n\n{after-help}",
+    help_template = "USAGE: [OPTIONS] [FILTERS]...\n\n{all-args}\n\n\n{after-help}",
     disable_version_flag = true,
+    args_override_self = true,
     after_help = "By default, all tests are run in parallel. This can be altered with the \n\
         --test-threads flag when running tests (set it to 1).",
 )]
@@ -28,26 +31,111 @@ pub struct Arguments {
     #[clap(long = "--ignored", help = "Run ignored tests")]
     pub ignored: bool,
 
-    /// Run tests, but not benchmarks.
+    /// Filter out tests marked with [`Trial::with_should_panic`][crate::Trial::with_should_panic].
+    #[clap(long = "--exclude-should-panic", help = "Excludes tests marked as should_panic")]
+    pub exclude_should_panic: bool,
+
+    /// Run tests, but not benchmarks. Can also be specified as
+    /// `--no-benches`.
     #[clap(
         long = "--test",
+        alias = "no-benches",
         conflicts_with = "bench",
-        help = "Run tests and not benchmarks",
+        help = "Run tests and not benchmarks (alias: --no-benches)",
     )]
     pub test: bool,
 
-    /// Run benchmarks, but not tests.
-    #[clap(long = "--bench", help = "Run benchmarks instead of tests")]
+    /// Run benchmarks, but not tests. Can also be specified as `--no-tests`.
+    #[clap(
+        long = "--bench",
+        alias = "no-tests",
+        help = "Run benchmarks instead of tests (alias: --no-tests)",
+    )]
     pub bench: bool,
 
     /// Only list all tests and benchmarks.
     #[clap(long = "--list", help = "List all tests and benchmarks")]
     pub list: bool,
 
-    /// No-op, ignored (libtest-mimic always runs in no-capture mode)
-    #[clap(long = "--nocapture", help = "No-op (libtest-mimic always runs in no-capture mode)")]
+    /// With `--list`, print the list as a single JSON array instead of one
+    /// line per test.
+    #[clap(
+        long = "--json-array",
+        requires = "list",
+        help = "With --list, print all tests as a single JSON array",
+    )]
+    pub json_array: bool,
+
+    /// With `--list`, print only the names of the tests, one per line. This
+    /// is useful to pipe the list into other tools.
+    #[clap(
+        long = "--names-only",
+        requires = "list",
+        conflicts_with = "json-array",
+        help = "With --list, print only the test names, one per line",
+    )]
+    pub names_only: bool,
+
+    /// With `--list`, print the kind of each test and whether it is ignored
+    /// in aligned columns, followed by the number of tests and benchmarks.
+    #[clap(
+        long = "--verbose-list",
+        requires = "list",
+        conflicts_with_all = &["json-array", "names-only"],
+        help = "With --list, also print the kind and ignore status in columns",
+    )]
+    pub verbose_list: bool,
+
+    /// Print the tests that would run, with all filters applied, but do not
+    /// run them. Unlike `--list`, this shows which tests would be ignored.
+    #[clap(long = "--dry-run", help = "Print the tests that would run without running them")]
+    pub dry_run: bool,
+
+    /// Only print the effective configuration (see
+    /// [`to_toml`][Arguments::to_toml]) instead of running tests.
+    #[clap(
+        long = "--dump-config",
+        help = "Print the effective configuration as TOML and exit",
+    )]
+    pub dump_config: bool,
+
+    /// Disables `--strict-stdout` and `--capture`. Without those, output is
+    /// never captured, so this is a no-op.
+    #[clap(
+        long = "--nocapture",
+        help = "Don't capture output of tests (disables --strict-stdout and --capture)",
+    )]
     pub nocapture: bool,
 
+    /// Warn about tests that write to stdout (without `--nocapture`). The
+    /// written output is shown after the warning. This only works when tests
+    /// run in the main thread (`--test-threads=1`) and on Unix.
+    #[clap(
+        long = "--strict-stdout",
+        help = "Warn about tests writing to stdout (only with --test-threads=1, Unix only)",
+    )]
+    pub strict_stdout: bool,
+
+    /// Capture everything tests write to stdout and stderr and show it
+    /// together with the failure message of failed tests (output of passing
+    /// tests is discarded). Like `--strict-stdout`, this only works when tests
+    /// run in the main thread (`--test-threads=1`) and on Unix. Has no effect
+    /// with `--nocapture`.
+    #[clap(
+        long = "--capture",
+        help = "Capture output of tests and show it for failed tests \n\
+            (only with --test-threads=1, Unix only)",
+    )]
+    pub capture: bool,
+
+    /// Do not remove the temporary directory of a failed test created via
+    /// [`Trial::test_in_temp_dir`][crate::Trial::test_in_temp_dir].
+    #[clap(
+        long = "--keep-temp-on-failure",
+        help = "Keep the temporary directories of failed tests for debugging",
+    )]
+    pub keep_temp_on_failure: bool,
+
     /// If set, filters are matched exactly rather than by substring.
     #[clap(
         long = "--exact",
@@ -55,6 +143,48 @@ pub struct Arguments {
     )]
     pub exact: bool,
 
+    /// If set, `--skip` values are matched exactly, while the filter is still
+    /// matched by substring (unless `--exact` is set, too). `--filter-regex`
+    /// takes precedence.
+    #[clap(
+        long = "--skip-exact",
+        help = "Exactly match --skip values rather than by substring",
+    )]
+    pub skip_exact: bool,
+
+    /// If set, tests need to have all tags given by `--tag` instead of any of
+    /// them.
+    #[clap(long = "--all-tags", help = "Only run tests that have all tags given by --tag")]
+    pub all_tags: bool,
+
+    /// If set, filters match the leading `::`-separated segments of test
+    /// names, e.g. `parser::json` (or `parser::json::`) matches
+    /// `parser::json::nested`, but not `xparser::json` or
+    /// `parser::json5`. `--exact` and `--filter-regex` take precedence.
+    #[clap(
+        long = "--prefix",
+        help = "Match filters against the leading path segments (split at ::) of test names",
+    )]
+    pub prefix: bool,
+
+    /// If set, the filter and all `--skip` values are interpreted as regular
+    /// expressions (matching anywhere in the test name). Takes precedence
+    /// over `--exact`.
+    #[clap(
+        long = "--filter-regex",
+        help = "Interpret filters (including --skip) as regular expressions",
+    )]
+    pub filter_regex: bool,
+
+    /// If set, the filter and all `--skip` values match test names
+    /// regardless of their case. This works with `--exact` and
+    /// `--filter-regex` as well.
+    #[clap(
+        long = "--ignore-case",
+        help = "Match filters (including --skip) case-insensitively",
+    )]
+    pub ignore_case: bool,
+
     /// If set, display only one character per test instead of one line.
     /// Especially useful for huge test suites.
     ///
@@ -68,17 +198,250 @@ pub struct Arguments {
     )]
     pub quiet: bool,
 
+    /// If set, nothing is printed for passing, ignored or measured tests, only
+    /// the failures and the summary line. Useful for test suites too huge even
+    /// for `--quiet`.
+    #[clap(
+        short = 'Q',
+        long = "--silent",
+        conflicts_with_all = &["format", "quiet"],
+        help = "Only print failures and the summary line",
+    )]
+    pub silent: bool,
+
+    /// Run tests in random order. The seed is printed so that the order can
+    /// be reproduced with `--shuffle-seed`.
+    #[clap(long = "--shuffle", help = "Run tests in random order")]
+    pub shuffle: bool,
+
+    /// Sort tests by kind and name and print their outcomes in that order,
+    /// even when running tests in parallel. Outcomes of tests that finish
+    /// early are held back until all tests before them have finished.
+    #[clap(
+        long = "--ensure-ordered",
+        help = "Sort tests by kind and name and print outcomes in that order",
+    )]
+    pub ordered: bool,
+
+    /// Run tests that failed in the previous run (according to `--history`)
+    /// before all other tests.
+    #[clap(
+        long = "--failed-first",
+        requires = "history",
+        help = "Run tests that failed last time (see --history) first",
+    )]
+    pub failed_first: bool,
+
+    /// After all tests ran, run the passing tests created via
+    /// [`Trial::repeatable_test`][crate::Trial::repeatable_test] once more.
+    /// Tests failing then are reported as flaky and counted as failed.
+    #[clap(
+        long = "--verify-passes",
+        help = "Run passing (repeatable) tests again and report flaky ones",
+    )]
+    pub verify_passes: bool,
+
+    /// Add information about the machine (hostname, OS, architecture, number
+    /// of CPUs and the version of this crate) to the JSON event of the suite
+    /// start (`--format=json` and `--report-socket`).
+    #[clap(
+        long = "--include-host-info",
+        help = "Add information about the machine to the JSON suite event",
+    )]
+    pub include_host_info: bool,
+
+    /// Warn about test names containing characters of ambiguous or zero
+    /// width, which might be misaligned depending on the terminal.
+    #[clap(
+        long = "--strict-width",
+        help = "Warn about test names with characters of ambiguous or zero width",
+    )]
+    pub strict_width: bool,
+
+    /// Do not pad test names to the same width, i.e. print `test {name} ...`
+    /// with a single space. This is useful if one very long name would push
+    /// all outcomes far to the right, or if the output is read by tools.
+    #[clap(long = "--no-align", help = "Do not pad test names to the same width")]
+    pub no_align: bool,
+
+    /// Omit the `running N tests` header and the blank lines around the
+    /// summary line, e.g. when the output is embedded in the output of
+    /// another harness. Only affects the pretty and terse formats.
+    #[clap(
+        long = "--minimal-output",
+        help = "Do not print the header and the blank lines around the summary",
+    )]
+    pub minimal_output: bool,
+
+    /// After the summary line, print the counts of each kind (see
+    /// [`Trial::with_kind`][crate::Trial::with_kind]), if there is more than
+    /// one kind.
+    #[clap(long = "--summary-by-kind", help = "Print the number of passed/failed tests per kind")]
+    pub summary_by_kind: bool,
+
+    /// Do not treat a run without any tests (e.g. because a filter does not
+    /// match anything) as an error. By default, such a run prints a warning
+    /// and exits with code 2, see
+    /// [`Conclusion::exit_code`][crate::Conclusion::exit_code].
+    #[clap(
+        long = "--allow-empty",
+        help = "Do not exit with an error if no tests are left after filtering",
+    )]
+    pub allow_empty: bool,
+
+    /// Treat ignored tests as failures, e.g. to catch accidentally committed
+    /// `#[ignore]`s in CI. Ignored tests are still reported as ignored, only
+    /// [`Conclusion::has_failed`][crate::Conclusion::has_failed] and the exit
+    /// code change.
+    #[clap(
+        long = "--error-on-ignored",
+        help = "Exit with an error if any tests were ignored",
+    )]
+    pub error_on_ignored: bool,
+
+    /// Write the output to stdout in addition to the logfile, e.g. to show
+    /// the progress in CI while also archiving the log. The logfile is never
+    /// colorized in this case.
+    #[clap(
+        long = "--tee",
+        requires = "logfile",
+        help = "Write logs to stdout as well as to the logfile",
+    )]
+    pub tee: bool,
+
     // ============== OPTIONS =================================================
-    /// Number of threads used for parallel testing.
+    /// Number of threads used for parallel testing. If unset, the
+    /// `RUST_TEST_THREADS` environment variable is used like in libtest
+    /// (ignoring invalid values). If that is not set either or this is 0, the
+    /// number of logical CPUs is used.
     #[clap(
         long = "--test-threads",
-        help = "Number of threads used for running tests in parallel. If set to 1, \n\
-            all tests are run in the main thread.",
+        help = "Number of threads used for running tests in parallel (default: \n\
+            $RUST_TEST_THREADS or number of logical CPUs, 0: number of logical CPUs). If this \n\
+            resolves to 1, all tests are run in the main thread.",
     )]
     pub test_threads: Option<usize>,
 
+    /// With `--capture`, only show the first `N` bytes of what a test wrote
+    /// to stdout and stderr (each), followed by `... [truncated]`.
+    #[clap(
+        long = "--max-capture-bytes",
+        value_name = "N",
+        help = "Truncate the captured output of each test to N bytes per stream",
+    )]
+    pub max_capture_bytes: Option<usize>,
+
+    /// Runs the trials kind by kind: the trials of one kind still run in
+    /// parallel, but only after all trials of the previous kind finished.
+    /// Kinds run in the order they first appear in.
+    #[clap(
+        long = "--serial-kinds",
+        help = "Run the tests of each kind in parallel, but one kind after another",
+    )]
+    pub serial_kinds: bool,
+
+    /// Only show the first `N` lines of each message in the list of failures,
+    /// followed by `... (truncated, M more lines)`. This only applies to the
+    /// terminal (or the writer passed to
+    /// [`run_with_writer`][crate::run_with_writer]): the logfile and the JSON,
+    /// TAP and JUnit output always contain the whole message.
+    #[clap(
+        long = "--max-message-lines",
+        value_name = "N",
+        help = "Truncate each message in the list of failures to N lines",
+    )]
+    pub max_message_lines: Option<usize>,
+
+    /// Like `--max-message-lines`, but limits the number of bytes.
+    #[clap(
+        long = "--max-message-bytes",
+        value_name = "N",
+        help = "Truncate each message in the list of failures to N bytes",
+    )]
+    pub max_message_bytes: Option<usize>,
+
+    /// Number of times a failed test created via
+    /// [`Trial::repeatable_test`][crate::Trial::repeatable_test] is run again.
+    /// The test passes if any attempt passes, otherwise the message of the
+    /// last attempt is reported. Other tests can only run once and are never
+    /// retried.
+    #[clap(
+        long = "--retries",
+        value_name = "N",
+        default_value_t = 0,
+        help = "Run failed (repeatable) tests up to N more times",
+    )]
+    pub retries: u32,
+
+    /// Stop starting new tests once this many tests have failed, e.g. to bail
+    /// out early if something is badly broken while tolerating a few flaky
+    /// failures. `--failfast-after=1` stops at the first failure. Tests that
+    /// are already running still finish, the remaining ones are reported as
+    /// not run (see [`Conclusion::num_not_run`][crate::Conclusion::num_not_run]).
+    #[clap(
+        long = "--failfast-after",
+        value_name = "N",
+        help = "Stop running tests after N failures",
+    )]
+    pub fail_after: Option<u64>,
+
+    /// How many times benchmarks created via
+    /// [`Trial::bench_fn`][crate::Trial::bench_fn] call the benchmarked
+    /// function with `--bench`.
+    #[clap(
+        long = "--bench-samples",
+        value_name = "N",
+        default_value_t = 50,
+        help = "Number of samples taken by benchmarks timed by the harness",
+    )]
+    pub bench_samples: u32,
+
+    /// Percentage of failed tests (relative to all tests that passed or
+    /// failed) that is tolerated: if at most this many tests failed, the
+    /// application still exits successfully. All failures are still reported.
+    #[clap(
+        long = "--allow-failure-rate",
+        value_name = "PERCENT",
+        parse(try_from_str = parse_percentage),
+        help = "Exit successfully if at most PERCENT % of the tests failed",
+    )]
+    pub allow_failure_rate: Option<f64>,
+
+    /// Time after which a still running test is reported as failed.
+    ///
+    /// Tests cannot be aborted, so a test that timed out keeps running in the
+    /// background and its result is discarded. This is only enforced when
+    /// tests run in parallel: with `--test-threads=1`, tests run in the main
+    /// thread and nothing can interrupt them.
+    #[clap(
+        long = "--test-timeout",
+        value_name = "SECONDS",
+        parse(try_from_str = parse_seconds),
+        help = "Report tests running longer than SECONDS as failed. Not enforced \n\
+            when tests are run in the main thread (--test-threads=1).",
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Tests running longer than this are marked as slow in the output and
+    /// counted in [`Conclusion::num_slow`][crate::Conclusion::num_slow].
+    #[clap(
+        long = "--slow-threshold",
+        value_name = "SECONDS",
+        parse(try_from_str = parse_seconds),
+        help = "Mark tests running longer than SECONDS as slow",
+    )]
+    pub slow_threshold: Option<Duration>,
+
+    /// Seed used to shuffle the order of tests. Implies `--shuffle`.
+    #[clap(
+        long = "--shuffle-seed",
+        value_name = "SEED",
+        help = "Run tests in random order, determined by SEED (implies --shuffle)",
+    )]
+    pub shuffle_seed: Option<u64>,
+
     /// Path of the logfile. If specified, everything will be written into the
-    /// file instead of stdout.
+    /// file instead of stdout (or in addition to stdout with `--tee`).
     #[clap(
         long = "--logfile",
         value_name = "PATH",
@@ -86,6 +449,110 @@ pub struct Arguments {
     )]
     pub logfile: Option<String>,
 
+    /// Path of a file the final counts of the run are written to (as JSON).
+    /// With the `sigterm` feature, the counts gathered so far are written when
+    /// the process receives SIGTERM.
+    #[clap(
+        long = "--exit-status-file",
+        value_name = "PATH",
+        help = "Write the number of passed/failed/... tests as JSON to the specified file",
+    )]
+    pub exit_status_file: Option<String>,
+
+    /// Address of a TCP endpoint to stream JSON events to (in the format of
+    /// libtest's `--format=json`), in addition to the normal output. If the
+    /// connection fails, a warning is printed and the tests run anyway.
+    #[clap(
+        long = "--report-socket",
+        value_name = "ADDR",
+        help = "Additionally stream JSON events to the TCP endpoint ADDR",
+    )]
+    pub report_socket: Option<String>,
+
+    /// File descriptor (inherited from a parent process) to additionally
+    /// write JSON events to, like `--report-socket`. The parent can combine
+    /// the events of multiple test binaries with
+    /// [`aggregate_from`][crate::aggregate_from]. Only supported on Unix.
+    #[clap(
+        long = "--aggregate-fd",
+        value_name = "FD",
+        help = "Additionally write JSON events to the inherited file descriptor FD",
+    )]
+    pub aggregate_fd: Option<i32>,
+
+    /// Path of a JUnit XML report to write after all tests ran, in addition
+    /// to the normal output.
+    #[clap(
+        long = "--junit-xml",
+        value_name = "PATH",
+        help = "Additionally write a JUnit XML report to the specified file",
+    )]
+    pub junit_xml: Option<String>,
+
+    /// Path of a file recording whether each test passed or failed the last
+    /// time it ran. It is read before and updated after running the tests.
+    #[clap(
+        long = "--history",
+        value_name = "PATH",
+        help = "Record outcomes of tests in the specified file (used by --failed-first)",
+    )]
+    pub history: Option<String>,
+
+    /// Path of a file recording how long each test took the last time it
+    /// ran. It is read before and updated after running the tests.
+    #[clap(
+        long = "--timings",
+        value_name = "PATH",
+        help = "Record durations of tests in the specified file (used by --time-budget)",
+    )]
+    pub timings: Option<String>,
+
+    /// Only run as many tests as fit into this time, according to the
+    /// durations recorded in the `--timings` file. Tests are selected from
+    /// fastest to slowest, and the others count as filtered out. Tests
+    /// without recorded duration are always run.
+    #[clap(
+        long = "--time-budget",
+        value_name = "SECONDS",
+        requires = "timings",
+        parse(try_from_str = parse_seconds),
+        help = "Only run the fastest tests (see --timings) that fit into SECONDS",
+    )]
+    pub time_budget: Option<Duration>,
+
+    /// OTLP/HTTP endpoint (e.g. `http://localhost:4318`) to export one
+    /// OpenTelemetry span per test to after all tests ran. Only plain HTTP is
+    /// supported. Requires the `otlp` feature, otherwise only a warning is
+    /// printed.
+    #[clap(
+        long = "--otlp-endpoint",
+        value_name = "URL",
+        help = "Export tests as OpenTelemetry spans to the OTLP/HTTP endpoint URL",
+    )]
+    pub otlp_endpoint: Option<String>,
+
+    /// Path of a file the names of all tests that were filtered out (by
+    /// filters, `--skip`, `--kind`, `--ignored`, `--exclude-should-panic` or
+    /// `--partition`) are written to, one per line.
+    #[clap(
+        long = "--report-filtered",
+        value_name = "PATH",
+        help = "Write the names of filtered out tests to the specified file",
+    )]
+    pub report_filtered: Option<String>,
+
+    /// With `--list`, compare the list to the one saved in this file (the
+    /// output of a previous `--list`) instead of printing it. Added and
+    /// removed tests are printed and counted as failed, so that the process
+    /// exits with an error code if the list changed.
+    #[clap(
+        long = "--baseline",
+        value_name = "PATH",
+        requires = "list",
+        help = "With --list, report tests added or removed compared to the list in PATH",
+    )]
+    pub baseline: Option<String>,
+
     /// A list of filters. Tests whose names contain parts of any of these
     /// filters are skipped.
     #[clap(
@@ -96,66 +563,354 @@ pub struct Arguments {
     )]
     pub skip: Vec<String>,
 
+    /// Only run tests whose kind (see
+    /// [`Trial::with_kind`][crate::Trial::with_kind]) equals one of these.
+    /// Tests without kind are only run if an empty string is given.
+    #[clap(
+        long = "--kind",
+        value_name = "KIND",
+        number_of_values = 1,
+        help = "Only run tests of kind KIND (this flag can be used multiple times)",
+    )]
+    pub kinds: Vec<String>,
+
+    /// Only run tests that have any of these tags (see
+    /// [`Trial::with_tag`][crate::Trial::with_tag]), or all of them with
+    /// `--all-tags`.
+    #[clap(
+        long = "--tag",
+        value_name = "TAG",
+        number_of_values = 1,
+        help = "Only run tests with tag TAG (this flag can be used multiple times)",
+    )]
+    pub tags: Vec<String>,
+
+    /// Do not run tests that have any of these tags. Takes precedence over
+    /// `--tag`.
+    #[clap(
+        long = "--exclude-tag",
+        value_name = "TAG",
+        number_of_values = 1,
+        help = "Skip tests with tag TAG (this flag can be used multiple times)",
+    )]
+    pub exclude_tags: Vec<String>,
+
+    /// Only run one partition of the tests, to split a test suite across
+    /// multiple machines. The partition is selected after all other filters
+    /// are applied, and tests of other partitions count as filtered out.
+    #[clap(
+        long = "--partition",
+        value_name = "count:INDEX/TOTAL|hash:INDEX/TOTAL",
+        help = "Only run the INDEX-th of TOTAL partitions of the tests (INDEX starts at 1): \n\
+            - count = split tests into TOTAL contiguous chunks\n\
+            - hash = assign tests to partitions by hashing their names\n",
+    )]
+    pub partition: Option<Partition>,
+
     /// Specifies whether or not to color the output.
     #[clap(
         long = "--color",
         possible_values = &["auto", "always", "never"],
         value_name = "auto|always|never",
         help = "Configure coloring of output: \n\
-            - auto = colorize if stdout is a tty and tests are run on serially (default)\n\
+            - auto = colorize if stdout is a tty (default)\n\
             - always = always colorize output\n\
-            - never = never colorize output\n",
+            - never = never colorize output\n\
+            With auto, CLICOLOR_FORCE forces colors, otherwise NO_COLOR or CLICOLOR=0\n\
+            disable them.\n",
     )]
     pub color: Option<ColorSetting>,
 
     /// Specifies the format of the output.
     #[clap(
         long = "--format",
-        possible_values = &["pretty", "terse"],
-        value_name = "pretty|terse|json",
+        possible_values = &["pretty", "terse", "json", "tap", "junit"],
+        value_name = "pretty|terse|json|tap|junit",
         help = "Configure formatting of output: \n\
             - pretty = Print verbose output\n\
-            - terse = Display one character per test\n",
+            - terse = Display one character per test\n\
+            - json = Output a JSON document per event\n\
+            - tap = Output in the Test Anything Protocol (version 13)\n\
+            - junit = Output a JUnit XML report after all tests ran\n",
     )]
     pub format: Option<FormatSetting>,
 
+    /// Specifies the order tests are run (and listed) in, before
+    /// `--ensure-ordered`, `--shuffle` and `--failed-first` are applied.
+    #[clap(
+        long = "--sort-by",
+        possible_values = &["name", "kind", "none"],
+        value_name = "name|kind|none",
+        help = "Configure the order of tests: \n\
+            - name = sort by name\n\
+            - kind = sort by kind, then by name\n\
+            - none = keep the order they were passed in (default)\n",
+    )]
+    pub sort_by: Option<SortBy>,
+
     // ============== POSITIONAL VALUES =======================================
-    /// Filter string. Only tests which contain this string are run.
+    /// Filter strings. Only tests which contain any of these strings are run
+    /// (with `--exact`: whose name equals any of them). If empty, all tests
+    /// are run.
     #[clap(
-        name = "FILTER",
-        help = "The FILTER string is tested against the name of all tests, and only those tests \
-                whose names contain the filter are run.",
+        name = "FILTERS",
+        help = "The FILTERS are tested against the name of all tests, and only those tests \
+                whose names contain any of the filters are run.",
     )]
-    pub filter: Option<String>,
+    pub filters: Vec<String>,
+
+    // ============== NON-CLI SETTINGS ========================================
+    /// The noun used for a test in the human readable output, as singular and
+    /// plural form (e.g. `("check", "checks")`). Defaults to "test" and
+    /// "tests". This cannot be set via CLI, use
+    /// [`with_noun`][Arguments::with_noun] instead.
+    #[clap(skip)]
+    pub noun: Option<(String, String)>,
 }
 
 impl Arguments {
-    /// Parses the global CLI arguments given to the application.
+    /// Parses the global CLI arguments given to the application, preceded by
+    /// the arguments in the `LIBTEST_MIMIC_ARGS` environment variable (see
+    /// [`from_args_and_env`][Self::from_args_and_env]).
     ///
     /// If the parsing fails (due to incorrect CLI args), an error is shown and
     /// the application exits. If help is requested (`-h` or `--help`), a help
     /// message is shown and the application exits, too.
     pub fn from_args() -> Self {
-        Parser::parse()
+        let env = std::env::var("LIBTEST_MIMIC_ARGS").ok();
+        Self::from_args_and_env(std::env::args_os(), env.as_deref())
+    }
+
+    /// Like [`from_iter`][Self::from_iter], but the arguments in `env` (the
+    /// value of the `LIBTEST_MIMIC_ARGS` environment variable for
+    /// [`from_args`][Self::from_args]) are inserted before the given ones.
+    /// `env` is split into arguments like a shell would, supporting single
+    /// and double quotes and backslash escapes. If an option is given in both,
+    /// the one of `iter` wins; filters and repeatable options are combined.
+    pub fn from_args_and_env<I>(iter: I, env: Option<&str>) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<std::ffi::OsString> + Clone,
+    {
+        let env_args = match env.map(split_shell_words).transpose() {
+            Ok(env_args) => env_args.unwrap_or_default(),
+            Err(e) => {
+                let msg = format!("invalid LIBTEST_MIMIC_ARGS: {}", e);
+                Self::command().error(ErrorKind::InvalidValue, msg).exit();
+            }
+        };
+
+        let mut iter = iter.into_iter().map(Into::into);
+        let all = iter.next().into_iter()
+            .chain(env_args.into_iter().map(std::ffi::OsString::from))
+            .chain(iter);
+        Self::from_iter(all.collect::<Vec<_>>())
     }
 
     /// Like `from_args()`, but operates on an explicit iterator and not the
     /// global arguments. Note that the first element is the executable name!
+    #[allow(clippy::should_implement_trait)]
     pub fn from_iter<I>(iter: I) -> Self
     where
         Self: Sized,
         I: IntoIterator,
         I::Item: Into<std::ffi::OsString> + Clone,
     {
-        Parser::parse_from(iter)
+        let args: Self = Parser::parse_from(iter);
+        args.exit_if_invalid()
+    }
+
+    /// Returns these arguments as TOML document, which is what
+    /// `--dump-config` prints. The keys are the names of the long command line
+    /// flags, unset options are omitted. `--dump-config` itself and settings
+    /// that cannot be set via CLI (like [`noun`][Arguments::noun]) are not
+    /// included.
+    pub fn to_toml(&self) -> String {
+        crate::toml::render(self)
+    }
+
+    /// Sets the noun used for a test in the output, e.g. "running 3 checks",
+    /// "check foo ... ok" and "check result: ok". Useful when embedding this
+    /// library for things that are not tests.
+    pub fn with_noun(mut self, singular: impl Into<String>, plural: impl Into<String>) -> Self {
+        self.noun = Some((singular.into(), plural.into()));
+        self
+    }
+
+    /// Returns whether the harness colors its output, e.g. so that runners
+    /// can color their own messages the same way.
+    ///
+    /// Output to a logfile is only colored with `--color=always` (and never
+    /// with `--tee`, where this refers to stdout). For stdout, `--color=auto`
+    /// is first resolved via [`ColorSetting::resolve_env`], and then results
+    /// in colors if stdout is a terminal and `TERM` is not `dumb`.
+    pub fn use_color(&self) -> bool {
+        let color = self.color.unwrap_or(ColorSetting::Auto);
+        if self.logfile.is_some() && !self.tee {
+            return color == ColorSetting::Always;
+        }
+
+        match color.resolve_env(|var| std::env::var(var).ok()) {
+            ColorSetting::Always => true,
+            ColorSetting::Never => false,
+            ColorSetting::Auto => {
+                terminal_size::terminal_size().is_some()
+                    && std::env::var("TERM").map_or(true, |term| term != "dumb")
+            }
+        }
+    }
+
+    /// Checks that no contradictory flags are set, returning a message
+    /// describing the first problem found. When parsing the command line,
+    /// this is checked automatically, but arguments built in code are only
+    /// checked once they are passed to [`run`][crate::run]. If this fails
+    /// there, the message is printed like an invalid command line and the
+    /// process exits with code 2.
+    ///
+    /// These combinations are rejected:
+    /// - `--ignored` with `--include-ignored`
+    /// - `--test` with `--bench`
+    /// - `--quiet` or `--silent` with `--format`, and `--silent` with `--quiet`
+    /// - `--json-array`, `--names-only`, `--verbose-list` or `--baseline`
+    ///   without `--list`, and any two of the first three together
+    /// - `--failed-first` without `--history`
+    /// - `--time-budget` without `--timings`
+    /// - `--tee` without `--logfile`
+    ///
+    /// Other combinations are allowed and resolved like this: `--shuffle-seed`
+    /// implies `--shuffle`, `--filter-regex` takes precedence over `--exact`,
+    /// which takes precedence over `--prefix`, and `--list` ignores all
+    /// options about running tests.
+    pub fn validate(&self) -> Result<(), String> {
+        let conflicts = [
+            (self.ignored && self.include_ignored, "--ignored", "--include-ignored"),
+            (self.test && self.bench, "--test", "--bench"),
+            (self.quiet && self.format.is_some(), "--quiet", "--format"),
+            (self.silent && self.quiet, "--silent", "--quiet"),
+            (self.silent && self.format.is_some(), "--silent", "--format"),
+            (self.json_array && self.names_only, "--json-array", "--names-only"),
+            (self.verbose_list && self.json_array, "--verbose-list", "--json-array"),
+            (self.verbose_list && self.names_only, "--verbose-list", "--names-only"),
+        ];
+        if let Some((_, a, b)) = conflicts.iter().find(|c| c.0) {
+            return Err(format!("{} cannot be used with {}", a, b));
+        }
+
+        let requirements = [
+            (self.json_array && !self.list, "--json-array", "--list"),
+            (self.names_only && !self.list, "--names-only", "--list"),
+            (self.verbose_list && !self.list, "--verbose-list", "--list"),
+            (self.baseline.is_some() && !self.list, "--baseline", "--list"),
+            (self.failed_first && self.history.is_none(), "--failed-first", "--history"),
+            (self.time_budget.is_some() && self.timings.is_none(), "--time-budget", "--timings"),
+            (self.tee && self.logfile.is_none(), "--tee", "--logfile"),
+        ];
+        if let Some((_, a, b)) = requirements.iter().find(|r| r.0) {
+            return Err(format!("{} requires {}", a, b));
+        }
+
+        Ok(())
     }
+
+    /// Checks things that clap cannot check by itself and exits with a CLI
+    /// error if something is wrong.
+    fn exit_if_invalid(self) -> Self {
+        self.filter_or_exit();
+        self.exit_if_contradictory();
+
+        self
+    }
+
+    /// Builds the filter, exiting with a CLI error if a regex is invalid.
+    pub(crate) fn filter_or_exit(&self) -> Filter<'_> {
+        Filter::new(self).unwrap_or_else(|e| {
+            let msg = format!("invalid regex in filter: {}", e);
+            Self::command().error(ErrorKind::ValueValidation, msg).exit()
+        })
+    }
+
+    /// Exits with a CLI error if [`validate`][Self::validate] fails.
+    pub(crate) fn exit_if_contradictory(&self) {
+        if let Err(msg) = self.validate() {
+            Self::command().error(ErrorKind::ArgumentConflict, msg).exit();
+        }
+    }
+}
+
+/// Splits the given string into words like a POSIX shell, but without any
+/// expansions: words are separated by whitespace, single quotes preserve
+/// everything literally, and backslashes escape the next character (inside
+/// double quotes only `"` and `\`).
+fn split_shell_words(s: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = None::<String>;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated single quote".into()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => word.extend(['\\', c]),
+                            None => return Err("unterminated double quote".into()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated double quote".into()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err("trailing backslash".into()),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Parses a (possibly fractional) number of seconds, e.g. `2.5`.
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    let secs = s.parse::<f64>().map_err(|e| e.to_string())?;
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(format!("'{}' is not a valid number of seconds", s));
+    }
+
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// Parses a percentage between 0 and 100, e.g. `2.5`.
+fn parse_percentage(s: &str) -> Result<f64, String> {
+    let percent = s.parse::<f64>().map_err(|e| e.to_string())?;
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(format!("'{}' is not a percentage between 0 and 100", s));
+    }
+
+    Ok(percent)
 }
 
 /// Possible values for the `--color` option.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum ColorSetting {
-    /// Colorize output if stdout is a tty and tests are run on serially
-    /// (default).
+    /// Colorize output if `CLICOLOR_FORCE` is set (and not `0`), or else if
+    /// stdout is a tty and neither `NO_COLOR` nor `CLICOLOR=0` is set
+    /// (default). See [`resolve_env`][Self::resolve_env] for the details.
+    #[default]
     Auto,
 
     /// Always colorize output.
@@ -165,9 +920,38 @@ pub enum ColorSetting {
     Never,
 }
 
-impl Default for ColorSetting {
-    fn default() -> Self {
-        ColorSetting::Auto
+impl ColorSetting {
+    /// Resolves `Auto` according to the environment variables, following
+    /// [no-color.org](https://no-color.org) and the `CLICOLOR` conventions:
+    /// a non-empty `CLICOLOR_FORCE` other than `0` results in `Always`, a
+    /// non-empty `NO_COLOR` or `CLICOLOR=0` in `Never`. Otherwise, and for
+    /// explicit `Always` and `Never`, `self` is returned.
+    ///
+    /// `var` returns the value of the environment variable with the given
+    /// name, e.g. `|name| std::env::var(name).ok()`.
+    pub fn resolve_env(self, var: impl Fn(&str) -> Option<String>) -> Self {
+        if self != ColorSetting::Auto {
+            return self;
+        }
+
+        let var = |name| var(name).filter(|v| !v.is_empty());
+        if var("CLICOLOR_FORCE").map_or(false, |v| v != "0") {
+            ColorSetting::Always
+        } else if var("NO_COLOR").is_some() || var("CLICOLOR").map_or(false, |v| v == "0") {
+            ColorSetting::Never
+        } else {
+            ColorSetting::Auto
+        }
+    }
+}
+
+impl fmt::Display for ColorSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorSetting::Auto => "auto",
+            ColorSetting::Always => "always",
+            ColorSetting::Never => "never",
+        })
     }
 }
 
@@ -183,19 +967,139 @@ impl FromStr for ColorSetting {
     }
 }
 
-/// Possible values for the `--format` option.
+/// Possible values for the `--partition` option. `index` starts at 1 and is
+/// at most `total`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Partition {
+    /// Split the tests into `total` contiguous chunks of (roughly) equal size
+    /// and select the `index`-th one.
+    Count { index: u64, total: u64 },
+
+    /// Assign each test to one of `total` partitions based on the hash of its
+    /// name, and select the `index`-th one. Unlike `Count`, this keeps a test
+    /// in the same partition when other tests are added or removed.
+    Hash { index: u64, total: u64 },
+}
+
+impl Partition {
+    /// Returns whether the test with the given name and position (among
+    /// `num_tests` tests) is in this partition.
+    pub(crate) fn contains(&self, name: &str, position: usize, num_tests: usize) -> bool {
+        match *self {
+            Partition::Count { index, total } => {
+                let chunk = position as u64 * total / num_tests as u64;
+                chunk + 1 == index
+            }
+            Partition::Hash { index, total } => {
+                // FNV-1a, as it's simple and, unlike `DefaultHasher`, stable
+                // across Rust versions and platforms.
+                let hash = name.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+                    (hash ^ b as u64).wrapping_mul(0x100000001b3)
+                });
+                hash % total + 1 == index
+            }
+        }
+    }
+}
+
+impl fmt::Display for Partition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Partition::Count { index, total } => write!(f, "count:{}/{}", index, total),
+            Partition::Hash { index, total } => write!(f, "hash:{}/{}", index, total),
+        }
+    }
+}
+
+impl FromStr for Partition {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERR: &str = "invalid partition, expected `count:INDEX/TOTAL` or `hash:INDEX/TOTAL`";
+
+        let (kind, rest) = s.split_once(':').ok_or(ERR)?;
+        let (index, total) = rest.split_once('/').ok_or(ERR)?;
+        let index = index.parse::<u64>().map_err(|_| ERR)?;
+        let total = total.parse::<u64>().map_err(|_| ERR)?;
+        if index == 0 || index > total {
+            return Err("invalid partition, INDEX has to be between 1 and TOTAL");
+        }
+
+        match kind {
+            "count" => Ok(Partition::Count { index, total }),
+            "hash" => Ok(Partition::Hash { index, total }),
+            _ => Err(ERR),
+        }
+    }
+}
+
+/// Possible values for the `--sort-by` option.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortBy {
+    /// Sort tests by name.
+    Name,
+
+    /// Sort tests by kind, and tests of the same kind by name.
+    Kind,
+
+    /// Keep the order the tests were passed in. (default)
+    #[default]
+    None,
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SortBy::Name => "name",
+            SortBy::Kind => "kind",
+            SortBy::None => "none",
+        })
+    }
+}
+
+impl FromStr for SortBy {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(SortBy::Name),
+            "kind" => Ok(SortBy::Kind),
+            "none" => Ok(SortBy::None),
+            _ => Err("invalid sort order"),
+        }
+    }
+}
+
+/// Possible values for the `--format` option.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum FormatSetting {
     /// One line per test. Output for humans. (default)
+    #[default]
     Pretty,
 
     /// One character per test. Usefull for test suites with many tests.
     Terse,
+
+    /// One JSON object per line and event, like libtest's (unstable)
+    /// `--format=json`. Useful for tools processing the output.
+    Json,
+
+    /// The [Test Anything Protocol](https://testanything.org/) (version 13),
+    /// e.g. for `prove` and older CI tools.
+    Tap,
+
+    /// A JUnit XML report, written after all tests ran, like the one written
+    /// by `--junit-xml`. Useful for CI systems that read it from the output.
+    Junit,
 }
 
-impl Default for FormatSetting {
-    fn default() -> Self {
-        FormatSetting::Pretty
+impl fmt::Display for FormatSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FormatSetting::Pretty => "pretty",
+            FormatSetting::Terse => "terse",
+            FormatSetting::Json => "json",
+            FormatSetting::Tap => "tap",
+            FormatSetting::Junit => "junit",
+        })
     }
 }
 
@@ -205,6 +1109,9 @@ impl FromStr for FormatSetting {
         match s {
             "pretty" => Ok(FormatSetting::Pretty),
             "terse" => Ok(FormatSetting::Terse),
+            "json" => Ok(FormatSetting::Json),
+            "tap" => Ok(FormatSetting::Tap),
+            "junit" => Ok(FormatSetting::Junit),
             _ => Err("invalid output format"),
         }
     }
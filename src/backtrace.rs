@@ -0,0 +1,45 @@
+//! Capturing backtraces of panicking trials (only with the `backtrace`
+//! feature).
+//!
+//! A caught panic only carries its payload, so a panic hook captures the
+//! backtrace while the panicking thread is still unwinding and leaves it in a
+//! thread local, where the code catching the panic picks it up.
+
+// `std::backtrace` is only used with the `backtrace` feature.
+#![allow(clippy::incompatible_msrv)]
+
+use std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::RefCell,
+    panic,
+    sync::Once,
+};
+
+
+thread_local! {
+    static LAST: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Installs the panic hook, which calls the previous hook afterwards. Only
+/// installs it once per process.
+pub(crate) fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // Only captures anything if `RUST_BACKTRACE` (or
+            // `RUST_LIB_BACKTRACE`) is set.
+            let backtrace = Backtrace::capture();
+            if backtrace.status() == BacktraceStatus::Captured {
+                LAST.with(|last| *last.borrow_mut() = Some(backtrace));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Returns the backtrace of the last panic in this thread, if one was
+/// captured.
+pub(crate) fn take() -> Option<String> {
+    LAST.with(|last| last.borrow_mut().take()).map(|backtrace| backtrace.to_string())
+}
@@ -0,0 +1,130 @@
+//! Definition of the `Filter`.
+//!
+//! This decides which tests are filtered out, based on these parameters:
+//! - `filters`
+//! - `skip`
+//! - `kinds`
+//! - `tags`, `exclude_tags` and `all_tags`
+//! - `exact`, `skip_exact`, `prefix`, `filter_regex` and `ignore_case`
+//! - `ignored`
+//! - `exclude_should_panic`
+
+use std::borrow::Cow;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{Arguments, Trial};
+
+
+pub(crate) struct Filter<'a> {
+    args: &'a Arguments,
+    filters: Vec<Pattern<'a>>,
+    skip: Vec<Pattern<'a>>,
+}
+
+/// A single filter string, with the matching mode already decided.
+///
+/// With `--ignore-case`, substrings and exact names are stored in lowercase
+/// and compared to the lowercased test name.
+enum Pattern<'a> {
+    Substring(Cow<'a, str>),
+    Exact(Cow<'a, str>),
+    Prefix(Cow<'a, str>),
+    Regex(Regex),
+}
+
+impl<'a> Filter<'a> {
+    /// Creates a filter from the given arguments. Fails if `--filter-regex` is
+    /// set and one of the filters is not a valid regex.
+    pub(crate) fn new(args: &'a Arguments) -> Result<Self, regex::Error> {
+        let pattern = |s: &'a str, exact: bool| -> Result<Pattern<'a>, regex::Error> {
+            let folded = if args.ignore_case {
+                Cow::Owned(s.to_lowercase())
+            } else {
+                Cow::Borrowed(s)
+            };
+            if args.filter_regex {
+                RegexBuilder::new(s).case_insensitive(args.ignore_case).build().map(Pattern::Regex)
+            } else if exact {
+                Ok(Pattern::Exact(folded))
+            } else if args.prefix {
+                Ok(Pattern::Prefix(folded))
+            } else {
+                Ok(Pattern::Substring(folded))
+            }
+        };
+
+        Ok(Self {
+            args,
+            filters: args.filters.iter()
+                .map(|s| pattern(s, args.exact))
+                .collect::<Result<_, _>>()?,
+            skip: args.skip.iter()
+                .map(|s| pattern(s, args.exact || args.skip_exact))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub(crate) fn is_filtered_out(&self, test: &Trial) -> bool {
+        let test_name = if self.args.ignore_case {
+            Cow::Owned(test.info.name.to_lowercase())
+        } else {
+            Cow::Borrowed(test.info.name.as_str())
+        };
+        let test_name = &*test_name;
+
+        // If filters were specified, the test has to match any of them.
+        if !self.filters.is_empty() && !self.filters.iter().any(|f| f.matches(test_name)) {
+            return true;
+        }
+
+        // If any skip pattern were specified, test for all patterns.
+        if self.skip.iter().any(|skip_filter| skip_filter.matches(test_name)) {
+            return true;
+        }
+
+        if !self.args.kinds.is_empty() && !self.args.kinds.contains(&test.info.kind) {
+            return true;
+        }
+
+        let has_tag = |tag: &String| test.info.tags.contains(tag);
+        if !self.args.tags.is_empty() {
+            let has_tags = if self.args.all_tags {
+                self.args.tags.iter().all(has_tag)
+            } else {
+                self.args.tags.iter().any(has_tag)
+            };
+            if !has_tags {
+                return true;
+            }
+        }
+        if self.args.exclude_tags.iter().any(has_tag) {
+            return true;
+        }
+
+        if self.args.ignored && !test.info.is_ignored {
+            return true;
+        }
+
+        if self.args.exclude_should_panic && test.info.should_panic {
+            return true;
+        }
+
+        false
+    }
+}
+
+impl Pattern<'_> {
+    fn matches(&self, test_name: &str) -> bool {
+        match self {
+            Pattern::Substring(s) => test_name.contains(&**s),
+            Pattern::Exact(s) => test_name == s,
+            Pattern::Prefix(s) => {
+                let prefix = s.strip_suffix("::").unwrap_or(s);
+                test_name.strip_prefix(prefix)
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with("::"))
+            }
+            Pattern::Regex(regex) => regex.is_match(test_name),
+        }
+    }
+}
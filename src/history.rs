@@ -0,0 +1,64 @@
+//! Definition of the `History` used by `--history` and `--failed-first`.
+//!
+//! The history file has one line per test, `passed <name>` or
+//! `failed <name>`, describing the last outcome of that test. Tests whose
+//! names contain line breaks are not recorded.
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use crate::{Outcome, TrialResult};
+
+
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    /// Whether the test with the given name failed last time it ran.
+    failed: BTreeMap<String, bool>,
+}
+
+impl History {
+    /// Loads the history from the given file. A missing file is treated like
+    /// an empty history, as it is created by the first run.
+    pub(crate) fn load(path: &Path) -> io::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        let failed = content.lines()
+            .filter_map(|line| match line.split_once(' ') {
+                Some(("passed", name)) => Some((name.to_owned(), false)),
+                Some(("failed", name)) => Some((name.to_owned(), true)),
+                _ => None,
+            })
+            .collect();
+        Ok(Self { failed })
+    }
+
+    /// Returns whether the test with the given name failed last time.
+    pub(crate) fn has_failed(&self, name: &str) -> bool {
+        self.failed.get(name).copied().unwrap_or(false)
+    }
+
+    /// Records the outcomes of the given results. Ignored tests keep their
+    /// previous entry.
+    pub(crate) fn record(&mut self, results: &[TrialResult]) {
+        for result in results.iter().filter(|r| !r.name.contains(['\n', '\r'])) {
+            let failed = match result.outcome {
+                Outcome::Passed { .. } | Outcome::Measured(_) => false,
+                Outcome::Failed(_) => true,
+                Outcome::Ignored | Outcome::NotRun => continue,
+            };
+            self.failed.insert(result.name.clone(), failed);
+        }
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let content = self.failed.iter()
+            .map(|(name, &failed)| {
+                format!("{} {}\n", if failed { "failed" } else { "passed" }, name)
+            })
+            .collect::<String>();
+        fs::write(path, content)
+    }
+}
@@ -0,0 +1,151 @@
+//! JSON events in the format of libtest's (unstable) `--format=json`.
+//!
+//! Every function returns a single line (without trailing newline) describing
+//! one event.
+
+use std::{fmt::Write, time::Duration};
+
+use crate::{Conclusion, Measurement, Outcome, TestInfo};
+
+
+pub(crate) fn suite_started(num_tests: u64, include_host_info: bool) -> String {
+    let mut out = format!(r#"{{ "type": "suite", "event": "started", "test_count": {}"#, num_tests);
+    if include_host_info {
+        write!(
+            out,
+            r#", "host": {{ "hostname": "{}", "os": "{}", "arch": "{}", "cpus": {}, "#,
+            escape(&hostname()),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            num_cpus::get(),
+        ).unwrap();
+        write!(out, r#""libtest_mimic_version": "{}" }}"#, env!("CARGO_PKG_VERSION")).unwrap();
+    }
+    out.push_str(" }");
+    out
+}
+
+/// Returns the name of this machine, from the environment or (on Linux)
+/// `/etc/hostname`, or an empty string if it is unknown.
+fn hostname() -> String {
+    ["HOSTNAME", "COMPUTERNAME"].iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_owned())
+        .unwrap_or_default()
+}
+
+pub(crate) fn test_started(info: &TestInfo) -> String {
+    format!(
+        r#"{{ "type": "test", "event": "started", "name": "{}" }}"#,
+        escape(&info.name),
+    )
+}
+
+pub(crate) fn test_finished(info: &TestInfo, outcome: &Outcome) -> String {
+    let name = escape(&info.name);
+    match outcome {
+        Outcome::Passed { .. } => {
+            format!(r#"{{ "type": "test", "name": "{}", "event": "ok" }}"#, name)
+        }
+        Outcome::Failed(failed) => {
+            let mut out = format!(r#"{{ "type": "test", "name": "{}", "event": "failed""#, name);
+            if let Some(msg) = &failed.msg {
+                write!(out, r#", "stdout": "{}""#, escape(msg)).unwrap();
+            }
+            out.push_str(" }");
+            out
+        }
+        Outcome::Ignored => {
+            let mut out = format!(r#"{{ "type": "test", "name": "{}", "event": "ignored""#, name);
+            if let Some(reason) = &info.ignore_reason {
+                write!(out, r#", "message": "{}""#, escape(reason)).unwrap();
+            }
+            out.push_str(" }");
+            out
+        }
+        // libtest has no such event, so it is reported as ignored.
+        Outcome::NotRun => format!(
+            r#"{{ "type": "test", "name": "{}", "event": "ignored", "message": "not run" }}"#,
+            name,
+        ),
+        Outcome::Measured(Measurement { avg, variance }) => {
+            format!(
+                r#"{{ "type": "bench", "name": "{}", "median": {}, "deviation": {} }}"#,
+                name,
+                avg,
+                variance,
+            )
+        }
+    }
+}
+
+pub(crate) fn suite_finished(conclusion: &Conclusion, execution_time: Duration) -> String {
+    format!(
+        concat!(
+            r#"{{ "type": "suite", "event": "{}", "passed": {}, "failed": {}, "ignored": {}, "#,
+            r#""measured": {}, "filtered_out": {}, "exec_time": {:.3} }}"#,
+        ),
+        if conclusion.has_failed() { "failed" } else { "ok" },
+        conclusion.num_passed,
+        conclusion.num_failed,
+        conclusion.num_ignored,
+        conclusion.num_measured,
+        conclusion.num_filtered_out,
+        execution_time.as_secs_f64(),
+    )
+}
+
+/// Parses the counts of a `suite_finished` event. Returns `None` for all
+/// other lines. Only the exact format of this module is supported.
+pub(crate) fn parse_suite_finished(line: &str) -> Option<Conclusion> {
+    let line = line.trim();
+    let is_finished = line.starts_with(r#"{ "type": "suite", "event": "ok","#)
+        || line.starts_with(r#"{ "type": "suite", "event": "failed","#);
+    if !is_finished {
+        return None;
+    }
+
+    let field = |key: &str| -> Option<u64> {
+        let start = line.find(&format!(r#""{}": "#, key))? + key.len() + 4;
+        let len = line[start..].find(|c: char| !c.is_ascii_digit())?;
+        line[start..start + len].parse().ok()
+    };
+    Some(Conclusion {
+        num_filtered_out: field("filtered_out")?,
+        num_passed: field("passed")?,
+        num_failed: field("failed")?,
+        num_ignored: field("ignored")?,
+        num_measured: field("measured")?,
+        ..Conclusion::default()
+    })
+}
+
+/// Describes a test in a listing (`--list --format=json` and `--list
+/// --json-array`).
+pub(crate) fn list_entry(info: &TestInfo) -> String {
+    format!(
+        r#"{{ "type": "test", "name": "{}", "kind": "{}", "is_ignored": {}, "is_bench": {} }}"#,
+        escape(&info.name),
+        escape(&info.kind),
+        info.is_ignored,
+        info.is_bench,
+    )
+}
+
+/// Escapes the given string to be used inside a JSON string literal.
+pub(crate) fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out
+}
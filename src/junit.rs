@@ -0,0 +1,100 @@
+//! Writing JUnit XML reports (`--junit-xml`).
+//!
+//! There is no official specification of this format, but the structure used
+//! here is understood by all common CI systems (Jenkins, GitLab, ...).
+
+use std::{fmt::Write, time::Duration};
+
+use crate::{Conclusion, Outcome, TrialResult};
+
+
+/// Renders the whole JUnit XML document for the given results.
+pub(crate) fn render(
+    suite_name: &str,
+    results: &[TrialResult],
+    conclusion: &Conclusion,
+    execution_time: Duration,
+) -> String {
+    let mut out = String::new();
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(out, "<testsuites>").unwrap();
+    writeln!(
+        out,
+        r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="{}" time="{:.3}">"#,
+        escape(suite_name),
+        results.len(),
+        conclusion.num_failed,
+        conclusion.num_ignored + conclusion.num_not_run,
+        execution_time.as_secs_f64(),
+    ).unwrap();
+
+    for result in results {
+        // The kind is the closest thing to a class name we have.
+        let classname = if result.kind.is_empty() { suite_name } else { &result.kind };
+        write!(
+            out,
+            r#"    <testcase classname="{}" name="{}" time="{:.3}""#,
+            escape(classname),
+            escape(&result.name),
+            result.duration.as_secs_f64(),
+        ).unwrap();
+
+        match &result.outcome {
+            Outcome::Passed { .. } | Outcome::Measured(_) => writeln!(out, "/>").unwrap(),
+            Outcome::Ignored => {
+                writeln!(out, ">").unwrap();
+                writeln!(out, "      <skipped/>").unwrap();
+                writeln!(out, "    </testcase>").unwrap();
+            }
+            Outcome::NotRun => {
+                writeln!(out, ">").unwrap();
+                writeln!(out, r#"      <skipped message="not run"/>"#).unwrap();
+                writeln!(out, "    </testcase>").unwrap();
+            }
+            Outcome::Failed(failed) => {
+                writeln!(out, ">").unwrap();
+                match failed.message() {
+                    Some(msg) => writeln!(
+                        out,
+                        r#"      <failure message="{}">{}</failure>"#,
+                        escape(msg.lines().next().unwrap_or("")),
+                        escape(msg),
+                    ).unwrap(),
+                    None => writeln!(out, "      <failure/>").unwrap(),
+                }
+                writeln!(out, "    </testcase>").unwrap();
+            }
+        }
+    }
+
+    writeln!(out, "  </testsuite>").unwrap();
+    writeln!(out, "</testsuites>").unwrap();
+    out
+}
+
+/// Returns the name of the test suite: the file name of the executable.
+pub(crate) fn suite_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "tests".into())
+}
+
+/// Escapes the given string to be used in XML text and attribute values.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML
+            // 1.0 at all, not even escaped.
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => out.push('\u{FFFD}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
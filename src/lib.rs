@@ -61,23 +61,61 @@
 //!
 //! Some of the notable differences:
 //!
-//! - Output capture and `--nocapture`: simply not supported. The official
+//! - Output capture and `--nocapture`: not captured by default. The official
 //!   `libtest` uses internal `std` functions to temporarily redirect output.
-//!   `libtest-mimic` cannot use those. See [this issue][capture] for more
-//!   information.
-//! - `--format=json|junit`
+//!   `libtest-mimic` cannot use those. With `--capture`, the output is
+//!   captured on file descriptor level instead, but only on Unix and with
+//!   `--test-threads=1`. See [this issue][capture] for more information.
 //!
 //! [capture]: https://github.com/LukasKalbertodt/libtest-mimic/issues/9
 
-use std::{process, sync::mpsc, fmt, time::Instant};
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    io,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    path::Path,
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Condvar, Mutex,
+    },
+    fmt,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 
 mod args;
+#[cfg(feature = "backtrace")]
+mod backtrace;
+mod filter;
+mod history;
+mod json;
+mod junit;
+#[cfg(feature = "otlp")]
+mod otlp;
 mod printer;
+mod redirect;
+mod reporter;
+#[cfg(feature = "tokio")]
+mod runtime;
+mod status;
+mod temp_dir;
+mod timings;
+mod toml;
 
+use history::History;
 use printer::Printer;
-use threadpool::ThreadPool;
+use redirect::{Redirect, Stream};
+use status::StatusFile;
+use temp_dir::TempDir;
+use timings::Timings;
 
-pub use crate::args::{Arguments, ColorSetting, FormatSetting};
+pub use crate::{
+    args::{Arguments, ColorSetting, FormatSetting, Partition, SortBy},
+    reporter::Reporter,
+};
+pub use threadpool::{self, ThreadPool};
 
 
 
@@ -89,12 +127,68 @@ pub use crate::args::{Arguments, ColorSetting, FormatSetting};
 /// A trial is create via [`Trial::test`] or [`Trial::bench`]. The trial's
 /// `name` is printed and used for filtering. The `runner` is called when the
 /// test/benchmark is executed to determine its outcome. If `runner` panics,
-/// the trial is considered "failed". If you need the behavior of
-/// `#[should_panic]` you need to catch the panic yourself. You likely want to
-/// compare the panic payload to an expected value anyway.
+/// the trial is considered "failed", unless it is marked with
+/// [`Trial::with_should_panic`], which gives it the behavior of
+/// `#[should_panic]`.
 pub struct Trial {
-    runner: Box<dyn FnOnce(bool) -> Outcome + Send>,
+    runner: Box<dyn FnOnce(RunContext) -> Outcome + Send>,
     info: TestInfo,
+
+    /// Set for trials created via [`Trial::repeatable_test`], which can be
+    /// run more than once (see `--verify-passes`).
+    repeatable: Option<RepeatableRunner>,
+
+    /// Set for trials created via [`Trial::test_async`].
+    #[cfg(feature = "tokio")]
+    future: Option<runtime::AsyncRunner>,
+}
+
+type RepeatableRunner = Arc<dyn Fn(RunContext) -> Outcome + Send + Sync>;
+
+/// Settings of the run that are passed to each runner.
+#[derive(Debug, Clone)]
+struct RunContext {
+    /// See [`Trial::bench`].
+    test_mode: bool,
+
+    /// `--keep-temp-on-failure`
+    keep_temp_on_failure: bool,
+
+    /// `--bench-samples`, see [`Trial::bench_fn`].
+    bench_samples: u32,
+
+    /// See [`Trial::test_with_control`].
+    control: RunControl,
+}
+
+/// Allows a test to abort the whole run, see [`Trial::test_with_control`].
+#[derive(Debug, Clone, Default)]
+pub struct RunControl {
+    state: Arc<ControlState>,
+}
+
+#[derive(Debug, Default)]
+struct ControlState {
+    aborted: AtomicBool,
+}
+
+impl RunControl {
+    /// Requests that no further trials are started. Trials that are already
+    /// running are not interrupted.
+    pub fn abort(&self) {
+        self.state.aborted.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether [`abort`][Self::abort] was called during this run.
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Returns `Outcome::NotRun` if the run was aborted, so that the trial is
+    /// skipped.
+    fn skip_if_aborted(&self) -> Option<Outcome> {
+        self.is_aborted().then_some(Outcome::NotRun)
+    }
 }
 
 impl Trial {
@@ -107,8 +201,8 @@ impl Trial {
         R: FnOnce() -> Result<(), Failed> + Send + 'static,
     {
         Self {
-            runner: Box::new(move |_test_mode| match runner() {
-                Ok(()) => Outcome::Passed,
+            runner: Box::new(move |_| match runner() {
+                Ok(()) => Outcome::Passed { assertions: 0 },
                 Err(failed) => Outcome::Failed(failed),
             }),
             info: TestInfo {
@@ -116,7 +210,159 @@ impl Trial {
                 kind: String::new(),
                 is_ignored: false,
                 is_bench: false,
+                resource: None,
+                should_panic: false,
+                tags: Vec::new(),
+                ignore_reason: None,
             },
+            repeatable: None,
+            #[cfg(feature = "tokio")]
+            future: None,
+        }
+    }
+
+    /// Like [`Trial::test`], but the runner can be called multiple times. This
+    /// allows the harness to run the test again, which is done with
+    /// `--verify-passes`: passing repeatable tests are run a second time and
+    /// reported as flaky if they fail then.
+    pub fn repeatable_test<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: Fn() -> Result<(), Failed> + Send + Sync + 'static,
+    {
+        let repeatable: RepeatableRunner = Arc::new(move |_| match runner() {
+            Ok(()) => Outcome::Passed { assertions: 0 },
+            Err(failed) => Outcome::Failed(failed),
+        });
+        let runner = repeatable.clone();
+        Self {
+            runner: Box::new(move |ctx| runner(ctx)),
+            repeatable: Some(repeatable),
+            ..Self::test(name, || Ok(()))
+        }
+    }
+
+    /// Like [`Trial::test`], but the runner may return any error type that
+    /// implements `Display`. `Err(e)` is turned into a failure with message
+    /// `e.to_string()`, so functions returning e.g. `io::Result<()>` can be
+    /// used as runner directly.
+    pub fn test_result<R, E>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce() -> Result<(), E> + Send + 'static,
+        E: fmt::Display,
+    {
+        Self::test(name, move || runner().map_err(Failed::from))
+    }
+
+    /// Like [`Trial::test`], but the runner returns a future that is driven by
+    /// a tokio runtime. Only available with the `tokio` feature.
+    ///
+    /// With [`run_async`], async trials are spawned onto the given runtime and
+    /// `--test-threads` limits how many of them run at the same time. All
+    /// other `run_*` functions (and `run_async` with `--test-threads=1`) block
+    /// on the future instead, with a new single-threaded runtime per trial if
+    /// no runtime was given. Panics while creating or polling the future are
+    /// treated as failures.
+    #[cfg(feature = "tokio")]
+    pub fn test_async<R, F>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce() -> F + Send + 'static,
+        F: std::future::Future<Output = Result<(), Failed>> + Send + 'static,
+    {
+        let future: runtime::AsyncRunner = Box::new(move || {
+            let future = runner();
+            Box::pin(async move {
+                match future.await {
+                    Ok(()) => Outcome::Passed { assertions: 0 },
+                    Err(failed) => Outcome::Failed(failed),
+                }
+            })
+        });
+        Self {
+            future: Some(future),
+            ..Self::test(name, || Ok(()))
+        }
+    }
+
+    /// Like [`Trial::test`], but the runner reports how many assertions it
+    /// checked by returning `Ok(num_assertions)`. Failing runners can report
+    /// the number via [`Failed::with_assertions`]. The total number of
+    /// assertions of a run is stored in [`Conclusion::num_assertions`] and
+    /// printed in the summary line.
+    pub fn test_with_assertions<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce() -> Result<u64, Failed> + Send + 'static,
+    {
+        Self {
+            runner: Box::new(move |_| match runner() {
+                Ok(assertions) => Outcome::Passed { assertions },
+                Err(failed) => Outcome::Failed(failed),
+            }),
+            ..Self::test(name, || Ok(()))
+        }
+    }
+
+    /// Like [`Trial::test`], but the runner gets a [`RunControl`] that can be
+    /// used to abort the whole run, e.g. if a test finds that the environment
+    /// is unusable and all remaining tests would fail anyway. This is not tied
+    /// to the outcome of the test.
+    ///
+    /// After [`RunControl::abort`] was called, no further trials are started.
+    /// They are reported as ignored and a warning with their number is printed
+    /// at the end of the run.
+    pub fn test_with_control<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce(&RunControl) -> Result<(), Failed> + Send + 'static,
+    {
+        Self {
+            runner: Box::new(move |ctx| match runner(&ctx.control) {
+                Ok(()) => Outcome::Passed { assertions: 0 },
+                Err(failed) => Outcome::Failed(failed),
+            }),
+            ..Self::test(name, || Ok(()))
+        }
+    }
+
+    /// Like [`Trial::test`], but the runner gets the path of a new, empty
+    /// directory to use as scratch space. Every test gets its own directory
+    /// inside the system's temporary directory.
+    ///
+    /// The directory is removed after the test, unless the test failed and
+    /// `--keep-temp-on-failure` is set. In that case, the path is appended to
+    /// the failure message for debugging.
+    pub fn test_in_temp_dir<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce(&Path) -> Result<(), Failed> + Send + 'static,
+    {
+        let name = name.into();
+        let dir_name = name.clone();
+        Self {
+            runner: Box::new(move |ctx| {
+                let mut dir = match TempDir::create(&dir_name) {
+                    Ok(dir) => dir,
+                    Err(e) => return Outcome::Failed(
+                        format!("failed to create temporary directory: {}", e).into(),
+                    ),
+                };
+
+                // Panics are caught here (and not only in `run_single`) so
+                // that the directory can be kept for failed tests.
+                let result = catch_unwind(AssertUnwindSafe(|| runner(dir.path())))
+                    .unwrap_or_else(|e| Err(Failed::from_panic(&*e)));
+                match result {
+                    Ok(()) => Outcome::Passed { assertions: 0 },
+                    Err(failed) if ctx.keep_temp_on_failure => {
+                        dir.keep();
+                        let note = format!("temporary directory kept at {}", dir.path().display());
+                        let msg = match failed.msg {
+                            Some(msg) => format!("{}\n{}", msg, note),
+                            None => note,
+                        };
+                        Outcome::Failed(Failed { msg: Some(msg), ..failed })
+                    }
+                    Err(failed) => Outcome::Failed(failed),
+                }
+            }),
+            ..Self::test(name, || Ok(()))
         }
     }
 
@@ -137,9 +383,9 @@ impl Trial {
         R: FnOnce(bool) -> Result<Option<Measurement>, Failed> + Send + 'static,
     {
         Self {
-            runner: Box::new(move |test_mode| match runner(test_mode) {
+            runner: Box::new(move |RunContext { test_mode, .. }| match runner(test_mode) {
                 Err(failed) => Outcome::Failed(failed),
-                Ok(_) if test_mode => Outcome::Passed,
+                Ok(_) if test_mode => Outcome::Passed { assertions: 0 },
                 Ok(Some(measurement)) => Outcome::Measured(measurement),
                 Ok(None)
                     => Outcome::Failed("bench runner returned `Ok(None)` in bench mode".into()),
@@ -149,7 +395,44 @@ impl Trial {
                 kind: String::new(),
                 is_ignored: false,
                 is_bench: true,
+                resource: None,
+                should_panic: false,
+                tags: Vec::new(),
+                ignore_reason: None,
             },
+            repeatable: None,
+            #[cfg(feature = "tokio")]
+            future: None,
+        }
+    }
+
+    /// Creates a benchmark that is timed by the harness. The runner does any
+    /// setup and returns the function to benchmark.
+    ///
+    /// With `--bench`, the function is called `--bench-samples` times (50 by
+    /// default), timing each call. The benchmark's [`Measurement`] is the
+    /// average time of a call and the standard deviation (shown as "+/-").
+    /// Otherwise, the function is only called once to check that it does not
+    /// panic.
+    pub fn bench_fn<R, F>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce() -> Result<F, Failed> + Send + 'static,
+        F: FnMut(),
+    {
+        Self {
+            runner: Box::new(move |ctx| {
+                let mut f = match runner() {
+                    Ok(f) => f,
+                    Err(failed) => return Outcome::Failed(failed),
+                };
+                if ctx.test_mode {
+                    f();
+                    Outcome::Passed { assertions: 0 }
+                } else {
+                    Outcome::Measured(Measurement::sample(ctx.bench_samples, f))
+                }
+            }),
+            ..Self::bench(name, |_| Ok(None))
         }
     }
 
@@ -157,7 +440,8 @@ impl Trial {
     /// empty, it is printed in brackets before the test name (e.g.
     /// `test [my-kind] test_name`). (Default: *empty*)
     ///
-    /// This is the only extension to the original libtest.
+    /// libtest has no such concept; see `--kind` and `--summary-by-kind` to
+    /// filter and count trials by kind.
     pub fn with_kind(self, kind: impl Into<String>) -> Self {
         Self {
             info: TestInfo {
@@ -184,6 +468,99 @@ impl Trial {
         }
     }
 
+    /// Marks this test as "ignored". Shorthand for
+    /// [`with_ignored_flag(true)`][Trial::with_ignored_flag].
+    pub fn ignored(self) -> Self {
+        self.with_ignored_flag(true)
+    }
+
+    /// Marks this test as "ignored" and sets the reason, like
+    /// `#[ignore = "reason"]`. The reason is shown after the outcome (e.g.
+    /// `ignored, needs network`) when the test is not run.
+    pub fn with_ignore_reason(self, reason: impl Into<String>) -> Self {
+        Self {
+            info: TestInfo {
+                is_ignored: true,
+                ignore_reason: Some(reason.into()),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Replaces the name given to [`Trial::test`] or [`Trial::bench`]. Useful
+    /// to adjust names of trials that were created elsewhere.
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            info: TestInfo {
+                name: name.into(),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Sets the resource this trial needs exclusive access to, e.g. a fixed
+    /// port or a GPU. When running in parallel, at most one trial per
+    /// resource runs at a time, while trials using other (or no) resources
+    /// still run alongside. (Default: none)
+    ///
+    /// The time a trial waits for its resource does not count towards
+    /// `--test-timeout`. Instead, if the trial using the resource times out,
+    /// the trials waiting for it fail with "timed out waiting for resource",
+    /// so that they are not blocked forever.
+    pub fn with_resource(self, resource: impl Into<String>) -> Self {
+        Self {
+            info: TestInfo {
+                resource: Some(resource.into()),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Marks this test as expected to panic, like `#[should_panic]`: the test
+    /// passes if its runner panics and fails if it returns normally. If
+    /// `expected` is given, the panic message also has to contain it, like
+    /// `#[should_panic(expected = "...")]`. Tests marked like this are
+    /// filtered out by `--exclude-should-panic`.
+    ///
+    /// This has no effect on [`Trial::test_in_temp_dir`], which catches
+    /// panics of its runner itself.
+    pub fn with_should_panic(self, expected: Option<&str>) -> Self {
+        let expected = expected.map(str::to_owned);
+        let runner = self.runner;
+        let runner_expected = expected.clone();
+        let repeatable = self.repeatable.map(|repeatable| {
+            let expected = expected.clone();
+            Arc::new(move |ctx| {
+                let result = catch_unwind(AssertUnwindSafe(|| repeatable(ctx)));
+                expect_panic(result, expected.as_deref())
+            }) as RepeatableRunner
+        });
+        Self {
+            runner: Box::new(move |ctx| {
+                let result = catch_unwind(AssertUnwindSafe(|| runner(ctx)));
+                expect_panic(result, runner_expected.as_deref())
+            }),
+            repeatable,
+            #[cfg(feature = "tokio")]
+            future: self.future.map(|future| runtime::expect_panic(future, expected)),
+            info: TestInfo {
+                should_panic: true,
+                ..self.info
+            },
+        }
+    }
+
+    /// Adds a tag to this trial, e.g. `slow` or `network`. Unlike the kind,
+    /// tags are not printed, but a trial can have any number of them, and
+    /// they can be used to select trials with `--tag` and `--exclude-tag`.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.info.tags.push(tag.into());
+        self
+    }
+
     /// Returns the name of this trial.
     pub fn name(&self) -> &str {
         &self.info.name
@@ -195,11 +572,32 @@ impl Trial {
         &self.info.kind
     }
 
+    /// Returns the tags added via [`Trial::with_tag`].
+    pub fn tags(&self) -> &[String] {
+        &self.info.tags
+    }
+
+    /// Returns the resource set via [`Trial::with_resource`], if any.
+    pub fn resource(&self) -> Option<&str> {
+        self.info.resource.as_deref()
+    }
+
+    /// Returns whether this trial is expected to panic (see
+    /// [`Trial::with_should_panic`]).
+    pub fn should_panic(&self) -> bool {
+        self.info.should_panic
+    }
+
     /// Returns whether this trial has been marked as *ignored*.
     pub fn has_ignored_flag(&self) -> bool {
         self.info.is_ignored
     }
 
+    /// Returns the reason set via [`Trial::with_ignore_reason`], if any.
+    pub fn ignore_reason(&self) -> Option<&str> {
+        self.info.ignore_reason.as_deref()
+    }
+
     /// Returns `true` iff this trial is a test (as opposed to a benchmark).
     pub fn is_test(&self) -> bool {
         !self.info.is_bench
@@ -226,16 +624,24 @@ impl fmt::Debug for Trial {
             .field("kind", &self.info.kind)
             .field("is_ignored", &self.info.is_ignored)
             .field("is_bench", &self.info.is_bench)
+            .field("resource", &self.info.resource)
+            .field("should_panic", &self.info.should_panic)
+            .field("tags", &self.info.tags)
+            .field("ignore_reason", &self.info.ignore_reason)
             .finish()
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct TestInfo {
     name: String,
     kind: String,
     is_ignored: bool,
     is_bench: bool,
+    resource: Option<String>,
+    should_panic: bool,
+    tags: Vec<String>,
+    ignore_reason: Option<String>,
 }
 
 /// Output of a benchmark.
@@ -248,6 +654,28 @@ pub struct Measurement {
     pub variance: u64,
 }
 
+impl Measurement {
+    /// Calls `f` `samples` times (at least once) and returns the average time
+    /// and the standard deviation of the calls.
+    fn sample(samples: u32, mut f: impl FnMut()) -> Self {
+        let times = (0..samples.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                f();
+                start.elapsed().as_nanos() as f64
+            })
+            .collect::<Vec<_>>();
+
+        let n = times.len() as f64;
+        let avg = times.iter().sum::<f64>() / n;
+        let variance = times.iter().map(|t| (t - avg).powi(2)).sum::<f64>() / n;
+        Self {
+            avg: avg.round() as u64,
+            variance: variance.sqrt().round() as u64,
+        }
+    }
+}
+
 /// Indicates that a test/benchmark has failed. Optionally carries a message.
 ///
 /// You usually want to use the `From` impl of this type, which allows you to
@@ -255,35 +683,188 @@ pub struct Measurement {
 #[derive(Debug, Clone)]
 pub struct Failed {
     msg: Option<String>,
+    assertions: u64,
+
+    /// The `Debug` representations of the left and right value, if created
+    /// by [`mimic_assert_eq!`].
+    mismatch: Option<(String, String)>,
+
+    /// See [`Failed::backtrace`].
+    backtrace: Option<String>,
 }
 
 impl Failed {
     /// Creates an instance without message.
     pub fn without_message() -> Self {
-        Self { msg: None }
+        Self { msg: None, assertions: 0, mismatch: None, backtrace: None }
+    }
+
+    /// Creates an instance for two values that were expected to be equal, as
+    /// returned by [`mimic_assert_eq!`]. The message is formatted like the
+    /// one of [`assert_eq!`]; when printing the failures, the left value is
+    /// colored red and the right value green.
+    pub fn mismatch(left: &dyn fmt::Debug, right: &dyn fmt::Debug) -> Self {
+        let (left, right) = (format!("{:?}", left), format!("{:?}", right));
+        Self {
+            msg: Some(format!("assertion `left == right` failed{}", mismatch_lines(&left, &right))),
+            assertions: 0,
+            mismatch: Some((left, right)),
+            backtrace: None,
+        }
+    }
+
+    /// Prepends the given custom message (as passed to [`mimic_assert_eq!`])
+    /// to the message.
+    #[doc(hidden)]
+    pub fn with_context(self, context: fmt::Arguments) -> Self {
+        let msg = match self.msg {
+            Some(msg) => format!("{}: {}", context, msg),
+            None => context.to_string(),
+        };
+        Self { msg: Some(msg), ..self }
+    }
+
+    /// Sets the number of assertions the test checked before failing. See
+    /// [`Trial::test_with_assertions`]. (Default: 0)
+    pub fn with_assertions(self, assertions: u64) -> Self {
+        Self { assertions, ..self }
     }
 
     /// Returns the message of this instance.
     pub fn message(&self) -> Option<&str> {
         self.msg.as_deref()
     }
+
+    /// Returns the number of assertions the test checked before failing.
+    pub fn assertions(&self) -> u64 {
+        self.assertions
+    }
+
+    /// Returns the `Debug` representations of the left and right value if
+    /// this instance was created by [`Failed::mismatch`].
+    pub fn mismatched_values(&self) -> Option<(&str, &str)> {
+        self.mismatch.as_ref().map(|(left, right)| (left.as_str(), right.as_str()))
+    }
+
+    /// Returns the backtrace of the panic that made the test fail. Only
+    /// captured with the `backtrace` feature and if `RUST_BACKTRACE` is set;
+    /// printed below the message in the list of failures.
+    pub fn backtrace(&self) -> Option<&str> {
+        self.backtrace.as_deref()
+    }
+
+    /// Creates an instance for a caught panic, with the backtrace captured by
+    /// the panic hook (see the `backtrace` feature).
+    fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        let failed = Self::from(panic_message(payload));
+        #[cfg(feature = "backtrace")]
+        let failed = Self { backtrace: backtrace::take(), ..failed };
+        failed
+    }
+
+    /// Puts the captured output (see `--capture`) in front of the message,
+    /// like libtest does. Each stream is truncated to `max_bytes` bytes.
+    fn with_captured_output(
+        self,
+        stdout: Option<Vec<u8>>,
+        stderr: Option<Vec<u8>>,
+        max_bytes: Option<usize>,
+    ) -> Self {
+        let mut msg = String::new();
+        for mut output in [stdout, stderr].into_iter().flatten().filter(|o| !o.is_empty()) {
+            let truncated = match max_bytes {
+                Some(max) if output.len() > max => {
+                    output.truncate(max);
+                    true
+                }
+                _ => false,
+            };
+
+            msg.push_str(&String::from_utf8_lossy(&output));
+            if truncated {
+                msg.push_str("... [truncated]");
+            }
+            if !msg.ends_with('\n') {
+                msg.push('\n');
+            }
+        }
+        if msg.is_empty() {
+            return self;
+        }
+
+        match self.msg {
+            Some(m) => msg.push_str(&m),
+            // Without a message, the trailing newline is not needed.
+            None => {
+                msg.pop();
+            }
+        }
+        Self { msg: Some(msg), ..self }
+    }
 }
 
 impl<M: std::fmt::Display> From<M> for Failed {
     fn from(msg: M) -> Self {
         Self {
-            msg: Some(msg.to_string())
+            msg: Some(msg.to_string()),
+            assertions: 0,
+            mismatch: None,
+            backtrace: None,
         }
     }
 }
 
+/// The lines at the end of the message of [`Failed::mismatch`].
+fn mismatch_lines(left: &str, right: &str) -> String {
+    format!("\n  left: {}\n right: {}", left, right)
+}
+
+/// Like [`assert_eq!`], but instead of panicking, returns
+/// `Err(`[`Failed::mismatch`]`)` from the surrounding function, e.g. a runner
+/// passed to [`Trial::test`]. When printing the failures, the mismatching
+/// values are colored.
+///
+/// ```
+/// use libtest_mimic::{mimic_assert_eq, Trial};
+///
+/// let trial = Trial::test("addition", || {
+///     mimic_assert_eq!(1 + 1, 2);
+///     mimic_assert_eq!(2 + 2, 4, "math is broken");
+///     Ok(())
+/// });
+/// ```
+#[macro_export]
+macro_rules! mimic_assert_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    return ::std::result::Result::Err($crate::Failed::mismatch(left, right));
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    let failed = $crate::Failed::mismatch(left, right)
+                        .with_context(::std::format_args!($($arg)+));
+                    return ::std::result::Result::Err(failed);
+                }
+            }
+        }
+    };
+}
 
 
-/// The outcome of performing a test/benchmark.
+
+/// The outcome of performing a test/benchmark. See [`run_detailed`].
 #[derive(Debug, Clone)]
-enum Outcome {
-    /// The test passed.
-    Passed,
+pub enum Outcome {
+    /// The test passed, checking the given number of assertions (0 if the
+    /// test did not report any).
+    Passed { assertions: u64 },
 
     /// The test or benchmark failed.
     Failed(Failed),
@@ -293,6 +874,54 @@ enum Outcome {
 
     /// The benchmark was successfully run.
     Measured(Measurement),
+
+    /// The test or benchmark was not run because the run was aborted (see
+    /// [`RunControl::abort`] and `--failfast-after`).
+    NotRun,
+}
+
+impl Outcome {
+    /// Creates a failed outcome with the given message.
+    pub fn failed(msg: impl fmt::Display) -> Self {
+        Outcome::Failed(msg.into())
+    }
+
+    /// Creates a failed outcome from the given error. Unlike
+    /// [`Outcome::failed`], the message also contains the chain of
+    /// [`source`][std::error::Error::source]s of the error, one per line.
+    pub fn fail_with(err: &(dyn std::error::Error + 'static)) -> Self {
+        let mut msg = err.to_string();
+        let mut source = err.source();
+        while let Some(cause) = source {
+            msg.push_str(&format!("\ncaused by: {}", cause));
+            source = cause.source();
+        }
+        Outcome::Failed(msg.into())
+    }
+}
+
+/// The result of a single test/benchmark, as returned by [`run_detailed`].
+#[derive(Debug, Clone)]
+pub struct TrialResult {
+    /// Name of the trial.
+    pub name: String,
+
+    /// Kind of the trial (empty if none was set).
+    pub kind: String,
+
+    /// Whether the trial was marked as *ignored*. Note that this is not
+    /// necessarily the reason for `outcome` being `Ignored`, see
+    /// [`Trial::with_ignored_flag`].
+    pub has_ignored_flag: bool,
+
+    /// Whether the trial is a benchmark.
+    pub is_bench: bool,
+
+    /// How running the trial went.
+    pub outcome: Outcome,
+
+    /// How long running the trial took. Zero for ignored trials.
+    pub duration: Duration,
 }
 
 /// Contains information about the entire test run. Is returned by[`run`].
@@ -301,7 +930,14 @@ enum Outcome {
 /// [`exit()`][Conclusion::exit] on the result of `run` to exit the application
 /// with the correct exit code. But you can also store this value and inspect
 /// its data.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// New fields might be added in minor versions, so when creating an instance
+/// yourself (e.g. to compare it in a test), fill the remaining fields with
+/// `..Default::default()`.
+///
+/// Comparing conclusions ignores [`run_info`][Self::run_info], as it depends
+/// on timing and the machine the trials ran on.
+#[derive(Clone, Debug, Default, Eq)]
 #[must_use = "Call `exit()` or `exit_if_failed()` to set the correct return code"]
 pub struct Conclusion {
     /// Number of tests and benchmarks that were filtered out (either by the
@@ -319,74 +955,205 @@ pub struct Conclusion {
 
     /// Number of benchmarks that successfully ran.
     pub num_measured: u64,
+
+    /// Number of tests and benchmarks that were not run because the run was
+    /// aborted (see [`RunControl::abort`] and `--failfast-after`). They are
+    /// not counted as ignored.
+    pub num_not_run: u64,
+
+    /// Total number of assertions reported by tests. See
+    /// [`Trial::test_with_assertions`].
+    pub num_assertions: u64,
+
+    /// Number of failures that are tolerated without the run counting as
+    /// failed, as derived from `--allow-failure-rate`. [`exit`][Self::exit]
+    /// and [`exit_if_failed`][Self::exit_if_failed] only exit with an error
+    /// code if `num_failed` is larger than this. (Default: 0)
+    pub num_allowed_failures: u64,
+
+    /// Number of tests and benchmarks that ran longer than `--slow-threshold`.
+    pub num_slow: u64,
+
+    /// Whether a run without any tests is fine, as set by `--allow-empty`.
+    /// Otherwise, such a run counts as failed, see
+    /// [`exit_code`][Self::exit_code]. Always set for `--list` and
+    /// `--dry-run`, as they do not run anything. (Default: `false`)
+    pub allow_empty: bool,
+
+    /// Whether ignored tests count as failures, as set by
+    /// `--error-on-ignored`. See [`has_failed`][Self::has_failed]. (Default:
+    /// `false`)
+    pub ignored_is_error: bool,
+
+    /// How the run was carried out. Not set for `--list` and `--dry-run`.
+    /// Ignored when comparing conclusions.
+    pub run_info: RunInfo,
+}
+
+impl PartialEq for Conclusion {
+    fn eq(&self, other: &Self) -> bool {
+        // Destructured so that new fields cannot be forgotten here.
+        let Conclusion {
+            num_filtered_out,
+            num_passed,
+            num_failed,
+            num_ignored,
+            num_measured,
+            num_not_run,
+            num_assertions,
+            num_allowed_failures,
+            num_slow,
+            allow_empty,
+            ignored_is_error,
+            run_info: _,
+        } = self;
+        *num_filtered_out == other.num_filtered_out
+            && *num_passed == other.num_passed
+            && *num_failed == other.num_failed
+            && *num_ignored == other.num_ignored
+            && *num_measured == other.num_measured
+            && *num_not_run == other.num_not_run
+            && *num_assertions == other.num_assertions
+            && *num_allowed_failures == other.num_allowed_failures
+            && *num_slow == other.num_slow
+            && *allow_empty == other.allow_empty
+            && *ignored_is_error == other.ignored_is_error
+    }
+}
+
+/// How a run was carried out, after applying defaults. Part of
+/// [`Conclusion`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunInfo {
+    /// Total wall time of the run, including filtering and printing.
+    pub elapsed: Duration,
+
+    /// Number of threads used to run trials: 1 if they ran in the main
+    /// thread, otherwise the size of the thread pool (see `--test-threads` and
+    /// [`run_on_pool`]).
+    pub num_threads: usize,
+
+    /// The seed the trials were shuffled with, if they were shuffled.
+    pub shuffle_seed: Option<u64>,
 }
 
 impl Conclusion {
-    /// Exits the application with an appropriate error code (0 if all tests
-    /// have passed, 101 if there have been failures).
+    /// Returns the exit code the application should exit with: 101 if there
+    /// have been failures (more than allowed by `--allow-failure-rate`) or
+    /// ignored tests with `--error-on-ignored`, 2 if
+    /// no tests were left after filtering (unless `--allow-empty` is set), and
+    /// 0 otherwise.
+    pub fn exit_code(&self) -> i32 {
+        if self.num_failed > self.num_allowed_failures || self.has_failed_by_ignored() {
+            101
+        } else if !self.allow_empty && self.is_empty() {
+            2
+        } else {
+            0
+        }
+    }
+
+    /// Exits the application with an appropriate error code (see
+    /// [`exit_code`][Self::exit_code]).
     pub fn exit(&self) -> ! {
-        self.exit_if_failed();
-        process::exit(0);
+        process::exit(self.exit_code());
     }
 
-    /// Exits the application with error code 101 if there were any failures.
-    /// Otherwise, returns normally.
+    /// Exits the application with a non-zero error code if the run failed
+    /// (see [`exit_code`][Self::exit_code]). Otherwise, returns normally.
     pub fn exit_if_failed(&self) {
-        if self.has_failed() {
-            process::exit(101)
+        match self.exit_code() {
+            0 => {}
+            code => process::exit(code),
         }
     }
 
-    /// Returns whether there have been any failures.
-    pub fn has_failed(&self) -> bool {
-        self.num_failed > 0
+    /// Returns whether no tests were left after filtering, i.e. none passed,
+    /// failed, was ignored, measured or not run.
+    fn is_empty(&self) -> bool {
+        self.num_passed + self.num_failed + self.num_ignored + self.num_measured
+            + self.num_not_run == 0
     }
 
-    fn empty() -> Self {
-        Self {
-            num_filtered_out: 0,
-            num_passed: 0,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+    /// Combines the conclusions of two runs, e.g. of several groups of trials
+    /// that are run one after another, so that the program can exit once with
+    /// [`exit`][Self::exit]. All counts are added up, `allow_empty` is set if
+    /// it is set in both conclusions and `ignored_is_error` is set if it is set
+    /// in either. In
+    /// `run_info`, the elapsed times are added up, `num_threads` is the
+    /// maximum and `shuffle_seed` is taken from `self` if it is set there.
+    pub fn merge(&self, other: &Conclusion) -> Conclusion {
+        Conclusion {
+            num_filtered_out: self.num_filtered_out + other.num_filtered_out,
+            num_passed: self.num_passed + other.num_passed,
+            num_failed: self.num_failed + other.num_failed,
+            num_ignored: self.num_ignored + other.num_ignored,
+            num_measured: self.num_measured + other.num_measured,
+            num_not_run: self.num_not_run + other.num_not_run,
+            num_assertions: self.num_assertions + other.num_assertions,
+            num_allowed_failures: self.num_allowed_failures + other.num_allowed_failures,
+            num_slow: self.num_slow + other.num_slow,
+            allow_empty: self.allow_empty && other.allow_empty,
+            ignored_is_error: self.ignored_is_error || other.ignored_is_error,
+            run_info: RunInfo {
+                elapsed: self.run_info.elapsed + other.run_info.elapsed,
+                num_threads: self.run_info.num_threads.max(other.run_info.num_threads),
+                shuffle_seed: self.run_info.shuffle_seed.or(other.run_info.shuffle_seed),
+            },
         }
     }
-}
 
-impl Arguments {
-    /// Returns `true` if the given test should be ignored.
-    fn is_ignored(&self, test: &Trial) -> bool {
-        (test.info.is_ignored && !self.ignored && !self.include_ignored)
-            || (test.info.is_bench && self.test)
-            || (!test.info.is_bench && self.bench)
+    /// Returns whether there have been any failures. With
+    /// `--error-on-ignored`, ignored tests count as failures as well.
+    pub fn has_failed(&self) -> bool {
+        self.num_failed > 0 || self.has_failed_by_ignored()
     }
 
-    fn is_filtered_out(&self, test: &Trial) -> bool {
-        let test_name = &test.info.name;
+    fn has_failed_by_ignored(&self) -> bool {
+        self.ignored_is_error && self.num_ignored > 0
+    }
 
-        // If a filter was specified, apply this
-        if let Some(filter) = &self.filter {
-            match self.exact {
-                true if test_name != filter => return true,
-                false if !test_name.contains(filter) => return true,
-                _ => {}
-            };
-        }
+    /// Serializes the counts as a single line JSON object, e.g.
+    /// `{"passed":3,"failed":1,"ignored":0,"measured":0,"filtered_out":2}`.
+    /// This is independent of `--format` and meant for scripts and tools
+    /// wrapping a custom harness.
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"passed":{},"failed":{},"ignored":{},"measured":{},"filtered_out":{}}}"#,
+            self.num_passed,
+            self.num_failed,
+            self.num_ignored,
+            self.num_measured,
+            self.num_filtered_out,
+        )
+    }
+}
 
-        // If any skip pattern were specified, test for all patterns.
-        for skip_filter in &self.skip {
-            match self.exact {
-                true if test_name == skip_filter => return true,
-                false if test_name.contains(skip_filter) => return true,
-                _ => {}
-            }
+impl Arguments {
+    /// Returns the number of threads to run tests with: the value of
+    /// `--test-threads` or, if that is unset, of `RUST_TEST_THREADS`. Falls
+    /// back to the number of logical CPUs.
+    fn num_threads(&self) -> usize {
+        let from_env = || {
+            std::env::var("RUST_TEST_THREADS").ok()
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|&n| n > 0)
+        };
+        match self.test_threads.or_else(from_env) {
+            None | Some(0) => num_cpus::get().max(1),
+            Some(n) => n,
         }
+    }
 
-        if self.ignored && !test.info.is_ignored {
-            return true;
-        }
+    /// Returns `true` if the given test should be ignored.
+    fn is_ignored(&self, test: &Trial) -> bool {
+        self.is_ignored_info(&test.info)
+    }
 
-        false
+    fn is_ignored_info(&self, info: &TestInfo) -> bool {
+        (info.is_ignored && !self.ignored && !self.include_ignored)
+            || (info.is_bench && self.test)
+            || (!info.is_bench && self.bench)
     }
 }
 
@@ -397,118 +1164,1213 @@ impl Arguments {
 ///
 /// The returned value contains a couple of useful information. See
 /// [`Conclusion`] for more information. If `--list` was specified, a list is
-/// printed and a dummy `Conclusion` is returned.
-pub fn run(args: &Arguments, mut tests: Vec<Trial>) -> Conclusion {
+/// printed and a dummy `Conclusion` is returned. With `--baseline`, its
+/// `num_failed` is the number of tests added or removed.
+pub fn run(args: &Arguments, tests: Vec<Trial>) -> Conclusion {
+    run_inner(args, tests, |tests| Printer::new(args, tests), Hooks::default()).0
+}
+
+/// Like [`run`], but takes the trials from an iterator, e.g. one that lazily
+/// creates a trial per fixture file.
+///
+/// Trials are filtered as soon as the iterator yields them, so filtered out
+/// trials are dropped right away instead of being kept until all trials are
+/// created. The remaining trials are still collected before the first one
+/// runs: aligning the output (which needs the longest name), sorting,
+/// shuffling and `--partition` all need to know every trial.
+pub fn run_iter(args: &Arguments, tests: impl IntoIterator<Item = Trial>) -> Conclusion {
+    run_inner(args, tests, |tests| Printer::new(args, tests), Hooks::default()).0
+}
+
+/// Like [`run`], but additionally returns the result of every single trial
+/// that was not filtered out.
+///
+/// The results are in the order the trials finished, which is only
+/// deterministic when running in the main thread or with `--ensure-ordered`.
+/// If `--list` was specified, no trials are run and the list is empty.
+pub fn run_detailed(args: &Arguments, tests: Vec<Trial>) -> (Conclusion, Vec<TrialResult>) {
+    run_inner(args, tests, |tests| Printer::new(args, tests), Hooks::default())
+}
+
+/// Like [`run`], but the final set of trials is passed through `plan` before
+/// running them.
+///
+/// `plan` receives the trials that remain after filtering (and sorting or
+/// shuffling, if requested) and returns the trials that are actually run, in
+/// that order. It may reorder trials, change their ignored flag, remove
+/// trials or add new ones. Trials removed by `plan` are not counted as
+/// filtered out. `plan` is not called if `--list` is specified.
+pub fn run_with_plan(
+    args: &Arguments,
+    tests: Vec<Trial>,
+    plan: impl FnOnce(Vec<Trial>) -> Vec<Trial>,
+) -> Conclusion {
+    let hooks = Hooks {
+        plan: Some(Box::new(plan)),
+        ..Hooks::default()
+    };
+    run_inner(args, tests, |tests| Printer::new(args, tests), hooks).0
+}
+
+/// Like [`run`], but calls `before_all` once before the first trial runs and
+/// `after_all` once after the last trial finished. This is useful to set up
+/// and tear down fixtures shared by all trials, e.g. a server.
+///
+/// `before_all` is called after filtering, so it is also called if all
+/// trials were filtered out. `after_all` is called before the failures and
+/// the summary are printed, even if trials failed or panicked. Neither is
+/// called if `--list` is specified.
+pub fn run_with_setup(
+    args: &Arguments,
+    tests: Vec<Trial>,
+    before_all: impl FnOnce(),
+    after_all: impl FnOnce(),
+) -> Conclusion {
+    let hooks = Hooks {
+        before_all: Some(Box::new(before_all)),
+        after_all: Some(Box::new(after_all)),
+        ..Hooks::default()
+    };
+    run_inner(args, tests, |tests| Printer::new(args, tests), hooks).0
+}
+
+/// Like [`run`], but calls `on_kind_complete` with the kind of a trial (see
+/// [`Trial::with_kind`]) as soon as the last trial of that kind finished.
+/// This is useful to tear down resources used by all trials of one kind,
+/// e.g. a database for integration tests.
+///
+/// `on_kind_complete` is called exactly once per kind of the trials that are
+/// not filtered out (with an empty string for trials without kind), including
+/// ignored ones. It is not called if `--list` is specified.
+pub fn run_with_kind_hook(
+    args: &Arguments,
+    tests: Vec<Trial>,
+    on_kind_complete: impl FnMut(&str),
+) -> Conclusion {
+    let hooks = Hooks {
+        on_kind_complete: Some(Box::new(on_kind_complete)),
+        ..Hooks::default()
+    };
+    run_inner(args, tests, |tests| Printer::new(args, tests), hooks).0
+}
+
+/// Like [`run`], but calls `on_progress(completed, total)` every time a trial
+/// finished, e.g. to render a progress bar or to emit heartbeats on CI.
+///
+/// `total` is the number of trials that are not filtered out, including
+/// ignored ones. The callback is always called from the thread that called
+/// this function, even if the trials run in parallel. It is not called if
+/// `--list` is specified.
+pub fn run_with_progress(
+    args: &Arguments,
+    tests: Vec<Trial>,
+    on_progress: impl FnMut(usize, usize),
+) -> Conclusion {
+    let hooks = Hooks {
+        on_progress: Some(Box::new(on_progress)),
+        ..Hooks::default()
+    };
+    run_inner(args, tests, |tests| Printer::new(args, tests), hooks).0
+}
+
+/// Like [`run`], but all output is written to the given writer instead of
+/// stdout (or the logfile, which is ignored). This is useful to embed this
+/// harness into another application, e.g. to capture its output.
+///
+/// As it cannot be known whether the writer is a terminal, output is only
+/// colored (with ANSI escape codes) if `--color=always` is set.
+pub fn run_with_writer(args: &Arguments, tests: Vec<Trial>, out: impl io::Write) -> Conclusion {
+    run_inner(args, tests, |tests| Printer::with_writer(args, tests, out), Hooks::default()).0
+}
+
+/// Like [`run`], but the trials run on the given thread pool instead of a
+/// new one, e.g. to reuse the pool of an application or to configure its
+/// threads via [`threadpool::Builder`]. `--test-threads` is ignored, the size
+/// of the pool is used instead, and the trials never run in the main thread.
+///
+/// If a trial runs into `--test-timeout`, its thread cannot be stopped, so
+/// one thread is added to the pool to replace it.
+pub fn run_on_pool(args: &Arguments, tests: Vec<Trial>, pool: &ThreadPool) -> Conclusion {
+    let hooks = Hooks {
+        pool: Some(pool.clone()),
+        ..Hooks::default()
+    };
+    run_inner(args, tests, |tests| Printer::new(args, tests), hooks).0
+}
+
+/// Like [`run`], but async trials (see [`Trial::test_async`]) are run
+/// concurrently on a tokio runtime instead of each blocking a thread. Only
+/// available with the `tokio` feature.
+///
+/// `--test-threads` limits how many async trials run at the same time. If
+/// `runtime` is `None`, a multi-threaded runtime with that many worker
+/// threads is created for the run. Sync trials and async trials with a
+/// resource (see [`Trial::with_resource`]) still run in the thread pool, the
+/// latter blocking on the runtime. Async trials are never retried.
+///
+/// This function blocks until all trials finished, so it must not be called
+/// from within an async context.
+#[cfg(feature = "tokio")]
+pub fn run_async(
+    args: &Arguments,
+    tests: Vec<Trial>,
+    runtime: Option<&tokio::runtime::Handle>,
+) -> Conclusion {
+    let owned_runtime;
+    let handle = match runtime {
+        Some(handle) => handle.clone(),
+        None => {
+            owned_runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(args.num_threads())
+                .enable_all()
+                .build()
+                .expect("failed to build tokio runtime");
+            owned_runtime.handle().clone()
+        }
+    };
+    let hooks = Hooks {
+        runtime: Some(handle),
+        ..Hooks::default()
+    };
+    run_inner(args, tests, |tests| Printer::new(args, tests), hooks).0
+}
+
+/// Like [`run`], but all events are sent to the given [`Reporter`] instead
+/// of being printed, e.g. to show them in a GUI. Nothing is written to stdout
+/// or stderr, regardless of `--format`: warnings are passed to
+/// [`Reporter::warning`], while failure messages, captured output, the
+/// shuffle seed and the output of `--list` are dropped (the seed is still
+/// available in [`Conclusion::run_info`]).
+pub fn run_with_reporter(
+    args: &Arguments,
+    tests: Vec<Trial>,
+    reporter: impl Reporter,
+) -> Conclusion {
+    let make_printer = |tests: &[Trial]| Printer::with_reporter(args, tests, reporter);
+    run_inner(args, tests, make_printer, Hooks::default()).0
+}
+
+/// Like [`run_with_reporter`], but the trials run in a background thread and
+/// this returns right away, e.g. to show the results live in a TUI. The
+/// result of each trial is sent through the returned receiver as soon as the
+/// trial finished, and the returned handle yields the conclusion once all
+/// trials finished. Nothing is written to stdout or stderr, regardless of
+/// `--format`, and warnings are dropped.
+///
+/// The channel is unbounded, so the trials never wait for the receiver, but
+/// all results that were not received yet are kept in memory. Drain the
+/// receiver while the trials run (or drop it if the results are not needed).
+pub fn run_streaming(
+    args: &Arguments,
+    tests: Vec<Trial>,
+) -> (mpsc::Receiver<TrialResult>, thread::JoinHandle<Conclusion>) {
+    let args = args.clone();
+    let (sender, receiver) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        run_with_reporter(&args, tests, reporter::ChannelReporter(sender))
+    });
+    (receiver, handle)
+}
+
+/// Combines the JSON events written by multiple test binaries (e.g. via
+/// `--aggregate-fd` or `--format=json`) into one `Conclusion`, by adding up
+/// the counts of all suite summaries. All other lines are ignored, so the
+/// events of several binaries can be interleaved line by line.
+///
+/// Only the counts that are part of the summary event (passed, failed,
+/// ignored, measured and filtered out) are set.
+pub fn aggregate_from(reader: impl io::BufRead) -> io::Result<Conclusion> {
+    let mut conclusion = Conclusion::default();
+    for line in reader.lines() {
+        if let Some(c) = json::parse_suite_finished(&line?) {
+            conclusion = conclusion.merge(&c);
+        }
+    }
+    Ok(conclusion)
+}
+
+/// Callbacks of the different `run_*` variants.
+#[derive(Default)]
+struct Hooks<'h> {
+    /// See [`run_with_plan`].
+    plan: Option<PlanFn<'h>>,
+
+    /// See [`run_with_setup`].
+    before_all: Option<Box<dyn FnOnce() + 'h>>,
+
+    /// See [`run_with_setup`].
+    after_all: Option<Box<dyn FnOnce() + 'h>>,
+
+    /// See [`run_with_kind_hook`].
+    on_kind_complete: Option<KindFn<'h>>,
+
+    /// See [`run_with_progress`].
+    on_progress: Option<ProgressFn<'h>>,
+
+    /// See [`run_async`].
+    #[cfg(feature = "tokio")]
+    runtime: Option<tokio::runtime::Handle>,
+
+    /// See [`run_on_pool`].
+    pool: Option<ThreadPool>,
+}
+
+type PlanFn<'h> = Box<dyn FnOnce(Vec<Trial>) -> Vec<Trial> + 'h>;
+type KindFn<'h> = Box<dyn FnMut(&str) + 'h>;
+type ProgressFn<'h> = Box<dyn FnMut(usize, usize) + 'h>;
+
+fn run_inner<'a>(
+    args: &Arguments,
+    tests: impl IntoIterator<Item = Trial>,
+    make_printer: impl FnOnce(&[Trial]) -> Printer<'a>,
+    hooks: Hooks<'_>,
+) -> (Conclusion, Vec<TrialResult>) {
     let start_instant = Instant::now();
-    let mut conclusion = Conclusion::empty();
+    let mut conclusion = Conclusion::default();
+    args.exit_if_contradictory();
+    #[cfg(feature = "backtrace")]
+    backtrace::install_hook();
+
+    // Names of the tests filtered out, only collected for `--report-filtered`.
+    let mut filtered_out = Vec::new();
+    let mut report_filtered = |test: &Trial, keep: bool| {
+        if !keep && args.report_filtered.is_some() {
+            filtered_out.push(test.info.name.clone());
+        }
+        keep
+    };
+
+    // Apply filtering while collecting the trials, so that filtered out
+    // trials are dropped right away (see `run_iter`).
+    let needs_filter = !args.filters.is_empty()
+        || !args.skip.is_empty()
+        || !args.kinds.is_empty()
+        || !args.tags.is_empty()
+        || !args.exclude_tags.is_empty()
+        || args.ignored
+        || args.exclude_should_panic;
+    let filter = needs_filter.then(|| args.filter_or_exit());
+    let mut tests = tests.into_iter()
+        .filter(|test| {
+            let keep = filter.as_ref().map_or(true, |filter| !filter.is_filtered_out(test));
+            if !keep {
+                conclusion.num_filtered_out += 1;
+            }
+            report_filtered(test, keep)
+        })
+        .collect::<Vec<_>>();
+
+    // Sorting happens before partitioning, so that `--partition=count:...`
+    // does not depend on the order the tests were passed in either.
+    match args.sort_by.unwrap_or_default() {
+        SortBy::Name => tests.sort_by(|a, b| a.info.name.cmp(&b.info.name)),
+        SortBy::Kind => tests.sort_by(|a, b| {
+            (&a.info.kind, &a.info.name).cmp(&(&b.info.kind, &b.info.name))
+        }),
+        SortBy::None => {}
+    }
+
+    // Only keep the selected partition, if any.
+    if let Some(partition) = &args.partition {
+        let len_before = tests.len();
+        let mut position = 0;
+        tests.retain(|test| {
+            position += 1;
+            let keep = partition.contains(&test.info.name, position - 1, len_before);
+            report_filtered(test, keep)
+        });
+        conclusion.num_filtered_out += (len_before - tests.len()) as u64;
+    }
+
+    // With `--time-budget`, only keep the tests that fit into the budget
+    // according to the durations recorded last time.
+    let mut timings = args.timings.as_ref().map(|path| {
+        Timings::load(Path::new(path)).expect("failed to read timings file")
+    });
+    if let (Some(timings), Some(budget)) = (&timings, args.time_budget) {
+        for test in timings.select(&mut tests, budget) {
+            report_filtered(&test, false);
+            conclusion.num_filtered_out += 1;
+        }
+    }
 
-    // Apply filtering
-    if args.filter.is_some() || !args.skip.is_empty() || args.ignored {
-        let len_before = tests.len() as u64;
-        tests.retain(|test| !args.is_filtered_out(test));
-        conclusion.num_filtered_out = len_before - tests.len() as u64;
+    if let Some(path) = &args.report_filtered {
+        let content: String = filtered_out.iter().map(|name| format!("{}\n", name)).collect();
+        std::fs::write(path, content).expect("failed to write report of filtered tests");
     }
-    let tests = tests;
 
     // Create printer which is used for all output.
-    let mut printer = printer::Printer::new(args, &tests);
+    let mut printer = make_printer(&tests);
+
+    // If `--dump-config` is specified, just print the configuration and
+    // return.
+    if args.dump_config {
+        printer.print_config(&args.to_toml());
+        return (Conclusion::default(), Vec::new());
+    }
+
+    // With `--baseline`, compare the list to the saved one instead.
+    if let (true, Some(path)) = (args.list, &args.baseline) {
+        let baseline = std::fs::read_to_string(path).expect("failed to read baseline list");
+        let mut list = Vec::new();
+        Printer::write_list(&tests, args.ignored, &mut list).unwrap();
+        let list = String::from_utf8(list).unwrap();
+
+        let baseline = baseline.lines().filter(|l| !l.trim().is_empty()).collect::<Vec<_>>();
+        let added = list.lines().filter(|l| !baseline.contains(l)).collect::<Vec<_>>();
+        let removed = baseline.iter()
+            .filter(|l| !list.lines().any(|line| line == **l))
+            .copied()
+            .collect::<Vec<_>>();
+        printer.print_list_diff(&added, &removed);
+
+        let conclusion = Conclusion {
+            num_failed: (added.len() + removed.len()) as u64,
+            ..Conclusion::default()
+        };
+        return (conclusion, Vec::new());
+    }
 
     // If `--list` is specified, just print the list and return.
     if args.list {
-        printer.print_list(&tests, args.ignored);
-        return Conclusion::empty();
+        printer.print_list(&tests, args);
+        return (Conclusion { allow_empty: true, ..Conclusion::default() }, Vec::new());
+    }
+
+    // With `--ensure-ordered`, the order does not depend on the order the
+    // tests were passed in.
+    if args.ordered {
+        tests.sort_by(|a, b| {
+            (&a.info.kind, &a.info.name).cmp(&(&b.info.kind, &b.info.name))
+        });
+    }
+
+    // Shuffle tests if requested. This happens after filtering so that the
+    // same seed leads to the same order for the same selection of tests.
+    let mut shuffle_seed = None;
+    if args.shuffle || args.shuffle_seed.is_some() {
+        let seed = args.shuffle_seed.unwrap_or_else(|| {
+            let seed = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0);
+            printer.print_shuffle_seed(seed);
+            seed
+        });
+        shuffle(&mut tests, seed);
+        shuffle_seed = Some(seed);
+    }
+
+    // Load the history of previous runs and, with `--failed-first`, move
+    // tests that failed last time to the front (keeping the order otherwise).
+    let mut history = args.history.as_ref().map(|path| {
+        History::load(Path::new(path)).expect("failed to read history file")
+    });
+    if let Some(history) = history.as_ref().filter(|_| args.failed_first) {
+        tests.sort_by_key(|test| !history.has_failed(&test.info.name));
+    }
+
+    if let Some(plan) = hooks.plan {
+        tests = plan(tests);
+        printer.fit_columns(&tests);
+    }
+
+    // With `--dry-run`, only print the tests that would run. Like with
+    // `--list`, no hooks are called.
+    if args.dry_run {
+        printer.print_title(tests.len() as u64);
+        for test in &tests {
+            let ignored = args.is_ignored(test);
+            if ignored {
+                conclusion.num_ignored += 1;
+            }
+            printer.print_dry_run(&test.info, ignored);
+        }
+        printer.print_summary(&conclusion, start_instant.elapsed());
+        conclusion.allow_empty = true;
+        return (conclusion, Vec::new());
+    }
+
+    let status_file = args.exit_status_file.as_ref().map(StatusFile::new);
+    #[cfg(all(unix, feature = "sigterm"))]
+    let _sigterm_handler = status_file.as_ref().map(|status_file| {
+        status_file.install_sigterm_handler(printer.shared_output())
+            .expect("failed to install SIGTERM handler")
+    });
+
+    if let Some(before_all) = hooks.before_all {
+        before_all();
     }
 
     // Print number of tests
     printer.print_title(tests.len() as u64);
 
+    // Remember repeatable tests before they are consumed, to run them again
+    // with `--verify-passes`.
+    let repeatables = if args.verify_passes {
+        tests.iter()
+            .filter_map(|test| Some((test.info.clone(), test.repeatable.clone()?)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Number of trials per kind that did not finish yet, to call
+    // `on_kind_complete` after the last one.
+    let mut on_kind_complete = hooks.on_kind_complete;
+    let mut remaining_per_kind = HashMap::new();
+    if on_kind_complete.is_some() {
+        for test in &tests {
+            *remaining_per_kind.entry(test.info.kind.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let mut on_progress = hooks.on_progress;
+    let num_tests = tests.len();
+
     let mut failed_tests = Vec::new();
-    let mut handle_outcome = |outcome: Outcome, test: TestInfo, printer: &mut Printer| {
-        printer.print_single_outcome(&outcome);
+    let mut results = Vec::new();
+    #[cfg(feature = "otlp")]
+    let mut finished_at = Vec::new();
+    let control = RunControl::default();
+    let mut handle_outcome = |
+        outcome: Outcome,
+        duration: Duration,
+        retries: u32,
+        test: TestInfo,
+        printer: &mut Printer,
+    | {
+        printer.print_single_outcome(&test, &outcome, duration, retries);
+        if printer.is_slow(duration) {
+            conclusion.num_slow += 1;
+        }
+        results.push(TrialResult {
+            name: test.name.clone(),
+            kind: test.kind.clone(),
+            has_ignored_flag: test.is_ignored,
+            is_bench: test.is_bench,
+            outcome: outcome.clone(),
+            duration,
+        });
+        printer.test_finished(results.last().unwrap());
+        #[cfg(feature = "otlp")]
+        finished_at.push(SystemTime::now());
+
+        if let Some(on_progress) = &mut on_progress {
+            on_progress(results.len(), num_tests);
+        }
+        if let Some(on_kind_complete) = &mut on_kind_complete {
+            let remaining = remaining_per_kind.get_mut(&test.kind).unwrap();
+            *remaining -= 1;
+            if *remaining == 0 {
+                on_kind_complete(&test.kind);
+            }
+        }
 
         // Handle outcome
         match outcome {
-            Outcome::Passed => conclusion.num_passed += 1,
+            Outcome::Passed { assertions } => {
+                conclusion.num_passed += 1;
+                conclusion.num_assertions += assertions;
+            }
             Outcome::Failed(failed) => {
-                failed_tests.push((test, failed.msg));
+                conclusion.num_assertions += failed.assertions;
+                failed_tests.push((test, failed));
                 conclusion.num_failed += 1;
+                if args.fail_after.map_or(false, |n| conclusion.num_failed >= n) {
+                    control.abort();
+                }
             },
             Outcome::Ignored => conclusion.num_ignored += 1,
             Outcome::Measured(_) => conclusion.num_measured += 1,
+            Outcome::NotRun => conclusion.num_not_run += 1,
+        }
+
+        if let Some(status_file) = &status_file {
+            status_file.update(&conclusion);
         }
     };
 
+    // With `--serial-kinds`, the trials are grouped by kind. `groups`
+    // contains the group of each trial.
+    let groups = if args.serial_kinds {
+        group_by_kind(&mut tests)
+    } else {
+        vec![0; tests.len()]
+    };
+
     // Execute all tests.
-    let test_mode = !args.bench;
-    if args.test_threads == Some(1) {
+    let ctx = RunContext {
+        test_mode: !args.bench,
+        keep_temp_on_failure: args.keep_temp_on_failure,
+        bench_samples: args.bench_samples,
+        control: control.clone(),
+    };
+    let num_threads = hooks.pool.as_ref().map_or_else(|| args.num_threads(), ThreadPool::max_count);
+
+    // Async trials are only spawned onto the runtime when running in
+    // parallel. Otherwise, they block on their future like sync trials.
+    #[cfg(feature = "tokio")]
+    let spawner = hooks.runtime.as_ref()
+        .filter(|_| num_threads > 1 && !args.serial_kinds)
+        .map(|handle| runtime::Spawner::new(handle.clone(), num_threads, ctx.control.clone()));
+    #[cfg(feature = "tokio")]
+    for test in &mut tests {
+        if spawner.is_none() || test.info.resource.is_some() {
+            if let Some(future) = test.future.take() {
+                test.runner = runtime::blocking(future, hooks.runtime.clone());
+            }
+        }
+    }
+
+    if num_threads == 1 && hooks.pool.is_none() {
         // Run test sequentially in main thread
         for test in tests {
             // Print `test foo    ...`, run the test, then print the outcome in
             // the same line.
             printer.print_test(&test.info);
+            if !args.is_ignored(&test) && !ctx.control.is_aborted() {
+                printer.test_started(&test.info);
+            }
+            let mut stray_output = None;
+            let mut retries = 0;
+            let start = Instant::now();
             let outcome = if args.is_ignored(&test) {
                 Outcome::Ignored
+            } else if let Some(outcome) = ctx.control.skip_if_aborted() {
+                outcome
+            } else if (args.strict_stdout || args.capture) && !args.nocapture {
+                let stdout = Redirect::start(Stream::Stdout);
+                let stderr = args.capture.then(|| Redirect::start(Stream::Stderr));
+                let (outcome, n) = run_retrying(test.runner, test.repeatable, args.retries, &ctx);
+                retries = n;
+                let stderr = stderr.and_then(|r| r.and_then(|r| r.finish()).ok());
+                let stdout = stdout.and_then(|r| r.finish()).ok();
+
+                match outcome {
+                    // Captured output is shown with the failure message.
+                    Outcome::Failed(failed) if args.capture => {
+                        let max = args.max_capture_bytes;
+                        Outcome::Failed(failed.with_captured_output(stdout, stderr, max))
+                    }
+                    outcome => {
+                        if args.strict_stdout {
+                            stray_output = stdout;
+                        }
+                        outcome
+                    }
+                }
             } else {
-                run_single(test.runner, test_mode)
+                let (outcome, n) = run_retrying(test.runner, test.repeatable, args.retries, &ctx);
+                retries = n;
+                outcome
             };
-            handle_outcome(outcome, test.info, &mut printer);
+
+            let duration = match outcome {
+                Outcome::Ignored | Outcome::NotRun => Duration::ZERO,
+                _ => start.elapsed(),
+            };
+            let name = test.info.name.clone();
+            handle_outcome(outcome, duration, retries, test.info, &mut printer);
+            if let Some(output) = stray_output.filter(|o| !o.is_empty()) {
+                let msg = format!("test {} wrote to stdout without --nocapture", name);
+                printer.print_warning(&msg);
+                printer.print_raw(&output);
+            }
         }
     } else {
-        // Run test in thread pool.
-        let pool = ThreadPool::default();
+        if args.capture && !args.nocapture {
+            printer.print_warning("--capture only works with --test-threads=1, not capturing");
+        }
+
+        // Run test in thread pool. The workers of our own pool are named after
+        // the trial they run (see `run_in_thread`), so that profilers and
+        // debuggers show which trial is running. A pool passed to
+        // `run_on_pool` is used as is.
+        let name_threads = hooks.pool.is_none();
+        let mut pool = hooks.pool.unwrap_or_else(|| {
+            threadpool::Builder::new()
+                .num_threads(num_threads)
+                .thread_name(WORKER_NAME.to_owned())
+                .build()
+        });
         let (sender, receiver) = mpsc::channel();
 
-        let num_tests = tests.len();
-        for test in tests {
-            if args.is_ignored(&test) {
-                sender.send((Outcome::Ignored, test.info)).unwrap();
+        // The infos are kept here so that we can still report a test when its
+        // worker never sends a result (see `--test-timeout`).
+        let mut infos = Vec::with_capacity(tests.len());
+
+        // One lock per resource (see `Trial::with_resource`).
+        let mut resources = HashMap::<String, Arc<Resource>>::new();
+
+        // Number of unfinished trials per group (see `--serial-kinds`).
+        let gate = GroupGate::default();
+        let mut group_remaining = vec![0; groups.last().map_or(0, |group| group + 1)];
+        for &group in &groups {
+            group_remaining[group] += 1;
+        }
+        for (idx, test) in tests.into_iter().enumerate() {
+            #[cfg(feature = "tokio")]
+            let mut test = test;
+            #[cfg(feature = "tokio")]
+            let future = test.future.take();
+            let Trial { runner, info, repeatable, .. } = test;
+
+            if args.is_ignored_info(&info) {
+                let event = WorkerEvent::Finished(idx, Outcome::Ignored, Duration::ZERO, 0);
+                sender.send(event).unwrap();
             } else {
+                #[cfg(feature = "tokio")]
+                if let Some(future) = future {
+                    spawner.as_ref().unwrap().spawn(idx, future, sender.clone());
+                    infos.push(Some(info));
+                    continue;
+                }
+
                 let sender = sender.clone();
+                let max_retries = args.retries;
+                let timeout = args.timeout;
+                let resource = info.resource.as_ref().map(|name| {
+                    (name.clone(), resources.entry(name.clone()).or_default().clone())
+                });
+                let ctx = ctx.clone();
+                let thread_name = Some(info.name.clone()).filter(|_| name_threads);
+                let (gate, group) = (gate.clone(), groups[idx]);
                 pool.execute(move || {
+                    gate.wait_for(group);
+
                     // It's fine to ignore the result of sending. If the
                     // receiver has hung up, everything will wind down soon
                     // anyway.
-                    let outcome = run_single(test.runner, test_mode);
-                    let _ = sender.send((outcome, test.info));
+                    if let Some(outcome) = ctx.control.skip_if_aborted() {
+                        let _ = sender.send(WorkerEvent::Finished(idx, outcome, Duration::ZERO, 0));
+                        return;
+                    }
+                    let waiting_since = Instant::now();
+                    let _guard = match &resource {
+                        None => None,
+                        Some((name, resource)) => match resource.acquire(timeout) {
+                            Some(guard) => Some(guard),
+                            None => {
+                                let msg = format!("timed out waiting for resource `{}`", name);
+                                let outcome = Outcome::Failed(msg.into());
+                                let waited = waiting_since.elapsed();
+                                let _ = sender.send(WorkerEvent::Finished(idx, outcome, waited, 0));
+                                return;
+                            }
+                        },
+                    };
+                    let start = Instant::now();
+                    let _ = sender.send(WorkerEvent::Started(idx, start));
+                    let (outcome, retries) = run_in_thread(thread_name, move || {
+                        run_retrying(runner, repeatable, max_retries, &ctx)
+                    });
+                    let event = WorkerEvent::Finished(idx, outcome, start.elapsed(), retries);
+                    let _ = sender.send(event);
                 });
             }
+            infos.push(Some(info));
         }
 
-        for (outcome, test_info) in receiver.iter().take(num_tests) {
-            // In multithreaded mode, we do only print the start of the line
-            // after the test ran, as otherwise it would lead to terribly
-            // interleaved output.
-            printer.print_test(&test_info);
-            handle_outcome(outcome, test_info, &mut printer);
+        // Outcomes that arrived but have not been printed yet. Without
+        // `--ensure-ordered`, outcomes are printed right away.
+        let mut outcomes = infos.iter().map(|_| None).collect::<Vec<_>>();
+        let mut next_to_print = 0;
+
+        let mut running = HashMap::new();
+        let mut num_remaining = infos.len();
+        while num_remaining > 0 {
+            // Find the test that will time out next, if any.
+            let next_deadline = args.timeout.and_then(|timeout| {
+                running.iter()
+                    .map(|(&idx, &start): (&usize, &Instant)| (idx, start + timeout))
+                    .min_by_key(|&(_, deadline)| deadline)
+            });
+
+            let event = match next_deadline {
+                None => receiver.recv().unwrap(),
+                Some((idx, deadline)) => {
+                    let wait = deadline.saturating_duration_since(Instant::now());
+                    match receiver.recv_timeout(wait) {
+                        Ok(event) => event,
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            // We cannot abort the test, so its thread is just
+                            // abandoned. To not starve the remaining tests,
+                            // the pool gets a replacement thread.
+                            let timeout = args.timeout.unwrap();
+                            pool.set_num_threads(pool.max_count() + 1);
+                            let msg = format!(
+                                "test timed out after {:.2}s",
+                                timeout.as_secs_f64(),
+                            );
+                            WorkerEvent::Finished(idx, Outcome::Failed(msg.into()), timeout, 0)
+                        }
+                        Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!(),
+                    }
+                }
+            };
+
+            match event {
+                WorkerEvent::Started(idx, start) => {
+                    running.insert(idx, start);
+                    if let Some(test_info) = &infos[idx] {
+                        printer.test_started(test_info);
+                    }
+                    if printer.announces_test_start() {
+                        if let Some(test_info) = &infos[idx] {
+                            printer.print_test(test_info);
+                        }
+                    }
+                }
+                WorkerEvent::Finished(idx, outcome, duration, retries) => {
+                    let was_announced = running.remove(&idx).is_some()
+                        && printer.announces_test_start();
+
+                    // If the test has already timed out, its late result is
+                    // simply dropped.
+                    if infos[idx].is_none() || outcomes[idx].is_some() {
+                        continue;
+                    }
+                    outcomes[idx] = Some((outcome, duration, retries, was_announced));
+                    num_remaining -= 1;
+
+                    // Ignored trials finish right away, so later groups
+                    // might be done before the current one.
+                    group_remaining[groups[idx]] -= 1;
+                    if let Some(next) = group_remaining.iter().position(|&n| n > 0) {
+                        gate.open(next);
+                    }
+
+                    // With `--ensure-ordered`, we print all outcomes up to the
+                    // first test that is not finished yet.
+                    let to_print = if args.ordered {
+                        let end = outcomes[next_to_print..].iter()
+                            .position(|o| o.is_none())
+                            .map_or(outcomes.len(), |pos| next_to_print + pos);
+                        let range = next_to_print..end;
+                        next_to_print = end;
+                        range
+                    } else {
+                        idx..idx + 1
+                    };
+
+                    for idx in to_print {
+                        let test_info = infos[idx].take().unwrap();
+                        let (outcome, duration, retries, was_announced) =
+                            outcomes[idx].take().unwrap();
+
+                        // In multithreaded mode, we do only print the start of
+                        // the line after the test ran, as otherwise it would
+                        // lead to terribly interleaved output.
+                        if !was_announced {
+                            printer.print_test(&test_info);
+                        }
+                        handle_outcome(outcome, duration, retries, test_info, &mut printer);
+                    }
+                }
+            }
         }
     }
 
+    // Run passing repeatable tests again to detect flaky ones. Tests failing
+    // now are counted as failed, as the first result can't be trusted.
+    for (info, runner) in repeatables {
+        let result = results.iter_mut().find(|r| {
+            r.name == info.name
+                && r.kind == info.kind
+                && matches!(r.outcome, Outcome::Passed { .. })
+        });
+        let result = match result {
+            Some(result) => result,
+            None => continue,
+        };
+
+        let outcome = run_single(Box::new(move |ctx| runner(ctx)), ctx.clone());
+        if let Outcome::Failed(failed) = outcome {
+            printer.print_warning(&format!(
+                "test {} is flaky: it passed, but failed when run again (--verify-passes)",
+                info.name,
+            ));
+            let msg = match &failed.msg {
+                Some(msg) => format!("flaky, failed when run again: {}", msg),
+                None => "flaky, failed when run again".to_owned(),
+            };
+            conclusion.num_passed -= 1;
+            conclusion.num_failed += 1;
+            let failed = Failed { msg: Some(msg), ..failed };
+            result.outcome = Outcome::Failed(failed.clone());
+            failed_tests.push((info, failed));
+            if let Some(status_file) = &status_file {
+                status_file.update(&conclusion);
+            }
+        }
+    }
+
+    if let Some(after_all) = hooks.after_all {
+        after_all();
+    }
+
+    let num_not_run = conclusion.num_not_run;
+    if num_not_run > 0 {
+        let noun = if num_not_run == 1 { "test was" } else { "tests were" };
+        printer.print_warning(&format!("the run was aborted, {} {} not run", num_not_run, noun));
+    }
+
     // Print failures if there were any, and the final summary.
     if !failed_tests.is_empty() {
         printer.print_failures(&failed_tests);
     }
 
-    printer.print_summary(&conclusion, start_instant.elapsed());
+    let benches = results.iter()
+        .filter_map(|result| match &result.outcome {
+            Outcome::Measured(measurement) => Some((result.name.as_str(), measurement)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !benches.is_empty() {
+        printer.print_bench_table(&benches);
+    }
+
+    conclusion.ignored_is_error = args.error_on_ignored;
+    conclusion.allow_empty = args.allow_empty;
+    if !args.allow_empty && conclusion.is_empty() {
+        printer.print_warning("no tests to run");
+    }
+
+    if let Some(rate) = args.allow_failure_rate {
+        conclusion.num_allowed_failures =
+            allowed_failures(rate, conclusion.num_passed + conclusion.num_failed);
+    }
+
+    let execution_time = start_instant.elapsed();
+    conclusion.run_info = RunInfo {
+        elapsed: execution_time,
+        num_threads,
+        shuffle_seed,
+    };
+    printer.print_summary(&conclusion, execution_time);
+
+    if args.summary_by_kind {
+        let mut by_kind = BTreeMap::<&str, KindCounts>::new();
+        for result in &results {
+            let counts = by_kind.entry(&result.kind).or_default();
+            match result.outcome {
+                Outcome::Passed { .. } => counts.passed += 1,
+                Outcome::Failed(_) => counts.failed += 1,
+                Outcome::Ignored => counts.ignored += 1,
+                Outcome::Measured(_) => counts.measured += 1,
+                Outcome::NotRun => {}
+            }
+        }
+        if by_kind.keys().filter(|kind| !kind.is_empty()).count() > 1 {
+            printer.print_kind_summary(&by_kind);
+        }
+    }
+
+    if let Some(status_file) = &status_file {
+        status_file.finish(&conclusion).expect("failed to write exit status file");
+    }
+
+    if let Some(path) = &args.junit_xml {
+        let xml = junit::render(&junit::suite_name(), &results, &conclusion, execution_time);
+        std::fs::write(path, xml).expect("failed to write JUnit XML report");
+    }
+
+    // Exporting spans is best effort, failing tests because of an unreachable
+    // collector would not be helpful.
+    if let Some(endpoint) = &args.otlp_endpoint {
+        #[cfg(feature = "otlp")]
+        if let Err(e) = otlp::export(endpoint, &junit::suite_name(), &results, &finished_at) {
+            printer.print_warning(&format!("failed to export spans to {}: {}", endpoint, e));
+        }
+
+        #[cfg(not(feature = "otlp"))]
+        printer.print_warning(&format!(
+            "not exporting spans to {}: libtest-mimic was compiled without the `otlp` feature",
+            endpoint,
+        ));
+    }
+
+    if let (Some(history), Some(path)) = (&mut history, &args.history) {
+        history.record(&results);
+        history.save(Path::new(path)).expect("failed to write history file");
+    }
+    if let (Some(timings), Some(path)) = (&mut timings, &args.timings) {
+        timings.record(&results);
+        timings.save(Path::new(path)).expect("failed to write timings file");
+    }
+
+    (conclusion, results)
+}
+
+/// Shuffles the given tests with a Fisher-Yates shuffle. The random numbers
+/// are generated by splitmix64, which is plenty for this purpose and gives
+/// the same order for the same seed on all platforms.
+fn shuffle(tests: &mut [Trial], seed: u64) {
+    let mut state = seed;
+    for i in (1..tests.len()).rev() {
+        let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+        tests.swap(i, j);
+    }
+}
+
+/// Returns the next number of the SplitMix64 generator with the given state.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Number of trials of one kind per outcome, for `--summary-by-kind`.
+#[derive(Debug, Default)]
+struct KindCounts {
+    passed: u64,
+    failed: u64,
+    ignored: u64,
+    measured: u64,
+}
+
+/// Messages sent from the worker threads to the main thread.
+enum WorkerEvent {
+    /// The test with the given index started running at the given instant.
+    Started(usize, Instant),
+
+    /// The test with the given index finished with the given outcome after
+    /// running for the given duration and being retried the given number of
+    /// times.
+    Finished(usize, Outcome, Duration, u32),
+}
 
-    conclusion
+/// Returns the largest number of failures out of `num_run` trials that is at
+/// most `rate` percent.
+fn allowed_failures(rate: f64, num_run: u64) -> u64 {
+    // The small epsilon prevents rounding errors from cutting off an allowed
+    // failure exactly at the boundary (e.g. 0.29 * 100 = 28.999...).
+    (rate * num_run as f64 / 100.0 + 1e-9).floor() as u64
 }
 
 /// Runs the given runner, catching any panics and treating them as a failed test.
-fn run_single(runner: Box<dyn FnOnce(bool) -> Outcome + Send>, test_mode: bool) -> Outcome {
-    use std::panic::{catch_unwind, AssertUnwindSafe};
-
-    catch_unwind(AssertUnwindSafe(move || runner(test_mode))).unwrap_or_else(|e| {
-        // The `panic` information is just an `Any` object representing the
-        // value the panic was invoked with. For most panics (which use
-        // `panic!` like `println!`), this is either `&str` or `String`.
-        let payload = e.downcast_ref::<String>()
-            .map(|s| s.as_str())
-            .or(e.downcast_ref::<&str>().map(|s| *s));
-
-        let msg = match payload {
-            Some(payload) => format!("test panicked: {payload}"),
-            None => format!("test panicked"),
-        };
-        Outcome::Failed(msg.into())
-    })
+fn run_single(runner: Box<dyn FnOnce(RunContext) -> Outcome + Send>, ctx: RunContext) -> Outcome {
+    catch_unwind(AssertUnwindSafe(move || runner(ctx)))
+        .unwrap_or_else(|e| Outcome::Failed(Failed::from_panic(&*e)))
+}
+
+/// Name of the worker threads of our own pool while they are idle.
+const WORKER_NAME: &str = "libtest-mimic";
+
+/// Runs `f` in a thread with the given name, or as is if `name` is `None`.
+///
+/// Where supported, the current (worker) thread is renamed at the OS level
+/// while `f` runs, which is the name profilers and debuggers show.
+/// Otherwise, `f` runs in a new thread with the given name and this waits
+/// for it.
+fn run_in_thread<T: Send + 'static>(
+    name: Option<String>,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> T {
+    let name = match name {
+        Some(name) => name.replace('\0', ""),
+        None => return f(),
+    };
+
+    if set_os_thread_name(&name) {
+        let _reset = ResetThreadName;
+        return f();
+    }
+
+    thread::Builder::new()
+        .name(name)
+        .spawn(f)
+        .expect("failed to spawn test thread")
+        .join()
+        .unwrap_or_else(|e| resume_unwind(e))
+}
+
+/// Resets the OS-level name of the current worker thread when dropped, even
+/// if the trial panicked.
+struct ResetThreadName;
+
+impl Drop for ResetThreadName {
+    fn drop(&mut self) {
+        set_os_thread_name(WORKER_NAME);
+    }
+}
+
+/// Sets the name of the current thread as seen by the OS, but not by
+/// `thread::current().name()`. Returns whether that worked. `name` must not
+/// contain null bytes.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn set_os_thread_name(name: &str) -> bool {
+    // Linux only allows 15 bytes, macOS 63.
+    let max_len = if cfg!(target_os = "linux") { 15 } else { 63 };
+    let mut len = name.len().min(max_len);
+    while !name.is_char_boundary(len) {
+        len -= 1;
+    }
+    let name = std::ffi::CString::new(&name[..len]).expect("name contains null bytes");
+
+    // SAFETY: `name` is a valid C string of allowed length, and the thread
+    // handle refers to the current thread.
+    #[cfg(target_os = "linux")]
+    let result = unsafe { libc::pthread_setname_np(libc::pthread_self(), name.as_ptr()) };
+    #[cfg(target_os = "macos")]
+    let result = unsafe { libc::pthread_setname_np(name.as_ptr()) };
+    result == 0
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn set_os_thread_name(_name: &str) -> bool {
+    false
+}
+
+/// Stably sorts the trials by kind, in the order the kinds first appear, and
+/// returns the index of the group (kind) of each trial (`--serial-kinds`).
+fn group_by_kind(tests: &mut [Trial]) -> Vec<usize> {
+    let mut kinds = HashMap::new();
+    for test in tests.iter() {
+        let next = kinds.len();
+        kinds.entry(test.info.kind.clone()).or_insert(next);
+    }
+    tests.sort_by_key(|test| kinds[&test.info.kind]);
+    tests.iter().map(|test| kinds[&test.info.kind]).collect()
+}
+
+/// Lets the trials of a group (see `--serial-kinds`) wait in their worker
+/// thread until all trials of the previous groups finished. As trials are
+/// passed to the thread pool group by group, the waiting trials never block
+/// trials of an earlier group.
+#[derive(Clone, Default)]
+struct GroupGate(Arc<(Mutex<usize>, Condvar)>);
+
+impl GroupGate {
+    /// Blocks until `group` may run.
+    fn wait_for(&self, group: usize) {
+        let (current, condvar) = &*self.0;
+        let mut current = current.lock().unwrap_or_else(|e| e.into_inner());
+        while *current < group {
+            current = condvar.wait(current).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Lets all trials up to `group` run.
+    fn open(&self, group: usize) {
+        let (current, condvar) = &*self.0;
+        *current.lock().unwrap_or_else(|e| e.into_inner()) = group;
+        condvar.notify_all();
+    }
+}
+
+/// A resource that at most one trial uses at a time (see
+/// `Trial::with_resource`). It remembers since when it is in use, so that
+/// waiting trials can give up once the trial using it timed out.
+#[derive(Default)]
+struct Resource {
+    in_use_since: Mutex<Option<Instant>>,
+    released: Condvar,
+}
+
+impl Resource {
+    /// Blocks until the resource is free and takes it. With a `timeout`,
+    /// returns `None` instead once the trial using the resource has used it
+    /// for longer than that (see `--test-timeout`).
+    fn acquire(&self, timeout: Option<Duration>) -> Option<ResourceGuard<'_>> {
+        let mut in_use_since = self.in_use_since.lock().unwrap_or_else(|e| e.into_inner());
+        while let Some(since) = *in_use_since {
+            in_use_since = match timeout {
+                None => self.released.wait(in_use_since).unwrap_or_else(|e| e.into_inner()),
+                Some(timeout) => {
+                    let wait = (since + timeout).checked_duration_since(Instant::now())?;
+                    self.released.wait_timeout(in_use_since, wait)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+        }
+        *in_use_since = Some(Instant::now());
+        Some(ResourceGuard(self))
+    }
+}
+
+/// Frees the resource when dropped.
+struct ResourceGuard<'a>(&'a Resource);
+
+impl Drop for ResourceGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.in_use_since.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.0.released.notify_all();
+    }
+}
+
+/// Like `run_single`, but if the trial fails and can be run more than once,
+/// it is run again up to `retries` times (`--retries`). Returns the last
+/// outcome and the number of retries.
+fn run_retrying(
+    runner: Box<dyn FnOnce(RunContext) -> Outcome + Send>,
+    repeatable: Option<RepeatableRunner>,
+    retries: u32,
+    ctx: &RunContext,
+) -> (Outcome, u32) {
+    let mut outcome = run_single(runner, ctx.clone());
+    let mut num_retries = 0;
+    if let Some(repeatable) = repeatable {
+        while num_retries < retries && matches!(outcome, Outcome::Failed(_)) {
+            let repeatable = repeatable.clone();
+            outcome = run_single(Box::new(move |ctx| repeatable(ctx)), ctx.clone());
+            num_retries += 1;
+        }
+    }
+    (outcome, num_retries)
+}
+
+/// Turns the payload of a caught panic into a failure message.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match panic_payload_str(payload) {
+        Some(payload) => format!("test panicked: {payload}"),
+        None => "test panicked".to_string(),
+    }
+}
+
+/// Returns the message of a caught panic, if it has one.
+fn panic_payload_str(payload: &(dyn Any + Send)) -> Option<&str> {
+    // The `panic` information is just an `Any` object representing the
+    // value the panic was invoked with. For most panics (which use
+    // `panic!` like `println!`), this is either `&str` or `String`.
+    payload.downcast_ref::<String>()
+        .map(|s| s.as_str())
+        .or(payload.downcast_ref::<&str>().copied())
+}
+
+/// Determines the outcome of a trial marked with
+/// [`Trial::with_should_panic`] from the result of running it: a panic (with
+/// the expected message, if any) passes, finishing normally fails.
+fn expect_panic(result: thread::Result<Outcome>, expected: Option<&str>) -> Outcome {
+    let payload = match result {
+        Ok(Outcome::Passed { .. }) | Ok(Outcome::Measured(_)) => {
+            return Outcome::Failed("test did not panic as expected".into());
+        }
+        Ok(outcome) => return outcome,
+        Err(payload) => payload,
+    };
+
+    let msg = match (expected, panic_payload_str(&*payload)) {
+        (None, _) => return Outcome::Passed { assertions: 0 },
+        (Some(expected), Some(msg)) if msg.contains(expected) => {
+            return Outcome::Passed { assertions: 0 };
+        }
+        (Some(expected), Some(msg)) => format!(
+            "panic did not contain expected string\n      panic message: {:?},\n \
+                expected substring: {:?}",
+            msg,
+            expected,
+        ),
+        (Some(expected), None) => format!(
+            "expected panic with string value,\n found non-string value: {:?}\n     \
+                expected substring: {:?}",
+            (*payload).type_id(),
+            expected,
+        ),
+    };
+    Outcome::Failed(msg.into())
 }
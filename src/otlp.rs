@@ -0,0 +1,126 @@
+//! Exporting test results as OpenTelemetry spans (`--otlp-endpoint`, only
+//! with the `otlp` feature).
+//!
+//! Every test becomes one span, all spans of a run share one trace. They are
+//! sent in a single request using the JSON encoding of OTLP/HTTP. To not pull
+//! in an HTTP client, only plain `http://` endpoints are supported.
+
+use std::{
+    fmt::Write as _,
+    io::{self, Read, Write},
+    net::TcpStream,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{json, splitmix64, Outcome, TrialResult};
+
+
+/// Sends one span per result to the OTLP/HTTP collector at `endpoint` (e.g.
+/// `http://localhost:4318`). `finished_at` contains the time each trial
+/// finished, in the same order as `results`.
+pub(crate) fn export(
+    endpoint: &str,
+    service_name: &str,
+    results: &[TrialResult],
+    finished_at: &[SystemTime],
+) -> io::Result<()> {
+    let body = render(service_name, results, finished_at);
+    post(endpoint, "/v1/traces", &body)
+}
+
+fn render(service_name: &str, results: &[TrialResult], finished_at: &[SystemTime]) -> String {
+    let nanos = |t: SystemTime| t.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let mut rng = nanos(SystemTime::now()) as u64 ^ u64::from(std::process::id());
+    let trace_id = format!("{:016x}{:016x}", splitmix64(&mut rng), splitmix64(&mut rng));
+
+    let mut spans = Vec::with_capacity(results.len());
+    for (result, &end) in results.iter().zip(finished_at) {
+        let start = end.checked_sub(result.duration).unwrap_or(end);
+
+        // Status codes: 0 = unset, 1 = ok, 2 = error.
+        let (outcome, status) = match &result.outcome {
+            Outcome::Passed { .. } => ("passed", r#"{ "code": 1 }"#.to_owned()),
+            Outcome::Measured(_) => ("measured", r#"{ "code": 1 }"#.to_owned()),
+            Outcome::Ignored => ("ignored", r#"{ "code": 0 }"#.to_owned()),
+            Outcome::NotRun => ("not run", r#"{ "code": 0 }"#.to_owned()),
+            Outcome::Failed(failed) => {
+                let msg = json::escape(failed.message().unwrap_or(""));
+                ("failed", format!(r#"{{ "code": 2, "message": "{}" }}"#, msg))
+            }
+        };
+
+        let mut span = String::new();
+        write!(
+            span,
+            concat!(
+                r#"{{ "traceId": "{}", "spanId": "{:016x}", "name": "{}", "kind": 1, "#,
+                r#""startTimeUnixNano": "{}", "endTimeUnixNano": "{}", "attributes": [ "#,
+                r#"{{ "key": "test.kind", "value": {{ "stringValue": "{}" }} }}, "#,
+                r#"{{ "key": "test.outcome", "value": {{ "stringValue": "{}" }} }}, "#,
+                r#"{{ "key": "test.is_bench", "value": {{ "boolValue": {} }} }} ], "#,
+                r#""status": {} }}"#,
+            ),
+            trace_id,
+            splitmix64(&mut rng),
+            json::escape(&result.name),
+            nanos(start),
+            nanos(end),
+            json::escape(&result.kind),
+            outcome,
+            result.is_bench,
+            status,
+        ).unwrap();
+        spans.push(span);
+    }
+
+    format!(
+        concat!(
+            r#"{{ "resourceSpans": [ {{ "resource": {{ "attributes": [ "#,
+            r#"{{ "key": "service.name", "value": {{ "stringValue": "{}" }} }} ] }}, "#,
+            r#""scopeSpans": [ {{ "scope": {{ "name": "libtest-mimic", "version": "{}" }}, "#,
+            r#""spans": [ {} ] }} ] }} ] }}"#,
+        ),
+        json::escape(service_name),
+        env!("CARGO_PKG_VERSION"),
+        spans.join(", "),
+    )
+}
+
+/// Sends a minimal HTTP/1.1 POST request with a JSON body and checks that
+/// the response has a success status.
+fn post(endpoint: &str, path: &str, body: &str) -> io::Result<()> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg.to_owned());
+    let rest = endpoint.strip_prefix("http://")
+        .ok_or_else(|| invalid("only http:// OTLP endpoints are supported"))?;
+    let (host, base) = match rest.find('/') {
+        Some(pos) => rest.split_at(pos),
+        None => (rest, ""),
+    };
+    let host = if host.contains(':') { host.to_owned() } else { format!("{}:80", host) };
+
+    let mut stream = TcpStream::connect(&host)?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    write!(
+        stream,
+        concat!(
+            "POST {}{} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n",
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        ),
+        base.trim_end_matches('/'),
+        path,
+        host,
+        body.len(),
+        body,
+    )?;
+    stream.flush()?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response.split(' ').nth(1).unwrap_or("");
+    if status.starts_with('2') {
+        Ok(())
+    } else {
+        let line = response.lines().next().unwrap_or("no response");
+        Err(io::Error::new(io::ErrorKind::Other, format!("collector responded with {}", line)))
+    }
+}
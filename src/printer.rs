@@ -4,47 +4,198 @@
 //! (or logfile, if specified). These parameters influence printing:
 //! - `color`
 //! - `format` (and `quiet`)
-//! - `logfile`
+//! - `logfile` (and `tee`)
+//! - `report_socket`
+//! - `aggregate_fd`
 
-use std::{fs::File, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Write},
+    iter,
+    mem::{self, ManuallyDrop},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
 
 use termcolor::{Ansi, Color, ColorChoice, ColorSpec, NoColor, StandardStream, WriteColor};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::{
     Arguments, ColorSetting, Conclusion, FormatSetting, Outcome, Trial, Failed,
-    Measurement, TestInfo,
+    KindCounts, Measurement, Reporter, TestInfo, TrialResult, json, junit, mismatch_lines,
 };
 
-pub(crate) struct Printer {
-    out: Box<dyn WriteColor>,
+pub(crate) struct Printer<'a> {
+    out: Box<dyn WriteColor + 'a>,
+
+    /// The output, if it is stdout or the logfile, to flush it from the
+    /// SIGTERM handler.
+    shared_out: Option<SharedOutput>,
     format: FormatSetting,
     name_width: usize,
     kind_width: usize,
+
+    /// Whether the kind and name columns are padded to the same width
+    /// (disabled by `--no-align`).
+    align: bool,
+
+    /// Whether the header and the blank lines around the summary are omitted
+    /// (`--minimal-output`).
+    minimal: bool,
+
+    /// Whether nothing is printed for single tests, only failures and the
+    /// summary (`--silent`).
+    silent: bool,
+
+    /// Number of the last test line printed with `--format=tap`.
+    tap_number: u64,
+
+    /// Width of the terminal, if known. In terse mode, lines are wrapped at
+    /// this width and end with a `k/N` counter, like libtest does.
+    terse_width: Option<usize>,
+
+    /// Number of outcome characters in the current line in terse mode.
+    terse_column: usize,
+
+    /// When the output was last flushed in terse mode, which only flushes
+    /// every `TERSE_FLUSH_INTERVAL` to keep huge runs fast.
+    terse_flushed_at: Instant,
+
+    /// Number of tests that finished and the number of tests announced by
+    /// `print_title`, for the terse mode counter.
+    num_finished: u64,
+    num_tests: u64,
+
+    /// Singular and plural of the word used for "test".
+    noun: (String, String),
+
+    /// Messages in the list of failures are truncated to this many lines and
+    /// bytes (`--max-message-lines` and `--max-message-bytes`), but only on
+    /// the terminal.
+    max_message_lines: Option<usize>,
+    max_message_bytes: Option<usize>,
+    truncate_in: TruncateIn,
+
+    /// Tests running longer than this are marked as slow (`--slow-threshold`).
+    slow_threshold: Option<Duration>,
+
+    /// Flags that are repeated in the rerun hint after the failures, as they
+    /// decide whether the failed tests are run at all (see `rerun_flags`).
+    rerun_flags: Vec<String>,
+
+    /// Whether the suite event contains information about the machine
+    /// (`--include-host-info`).
+    include_host_info: bool,
+
+    /// If `--report-socket` is given, JSON events are additionally streamed
+    /// to this socket.
+    report_socket: Option<TcpStream>,
+
+    /// If `--aggregate-fd` is given, JSON events are additionally written to
+    /// this file descriptor. It is owned by the parent process and therefore
+    /// never closed.
+    aggregate_fd: Option<ManuallyDrop<File>>,
+
+    /// Receives all events, see `run_with_reporter`.
+    reporter: Option<Box<dyn Reporter + 'a>>,
+
+    /// With `--format=junit`, the results are collected here and only
+    /// printed by `print_summary`.
+    junit_results: Vec<TrialResult>,
 }
 
-impl Printer {
+impl<'a> Printer<'a> {
     /// Creates a new printer configured by the given arguments (`format`,
-    /// `quiet`, `color` and `logfile` options).
+    /// `quiet`, `color`, `logfile` and `tee` options). Whether the output is
+    /// colored is decided by [`Arguments::use_color`].
     pub(crate) fn new(args: &Arguments, tests: &[Trial]) -> Self {
         let color_arg = args.color.unwrap_or(ColorSetting::Auto);
+        let stdout = || {
+            let choice = if args.use_color() { ColorChoice::Always } else { ColorChoice::Never };
+            IgnoreBrokenPipe { inner: StandardStream::stdout(choice), closed: false }
+        };
 
         // Determine target of all output
-        let out = if let Some(logfile) = &args.logfile {
-            let f = File::create(logfile).expect("failed to create logfile");
-            if color_arg == ColorSetting::Always {
-                Box::new(Ansi::new(f)) as Box<dyn WriteColor>
-            } else {
-                Box::new(NoColor::new(f))
+        let tee_target = TeeTarget::default();
+        let truncate_in = match &args.logfile {
+            Some(_) if args.tee => TruncateIn::TeeFirst(tee_target.clone()),
+            Some(_) => TruncateIn::Nothing,
+            None => TruncateIn::Output,
+        };
+        let (out, terse_width): (Box<dyn WriteColor + Send>, _) = match &args.logfile {
+            Some(logfile) if args.tee => {
+                let f = File::create(logfile).expect("failed to create logfile");
+                (Box::new(Tee(stdout(), NoColor::new(f), tee_target.clone())), terminal_width())
             }
-        } else {
-            let choice = match color_arg {
-                ColorSetting::Auto => ColorChoice::Auto,
-                ColorSetting::Always => ColorChoice::Always,
-                ColorSetting::Never => ColorChoice::Never,
-            };
-            Box::new(StandardStream::stdout(choice))
+            Some(logfile) => {
+                let f = File::create(logfile).expect("failed to create logfile");
+                if color_arg == ColorSetting::Always {
+                    (Box::new(Ansi::new(f)), None)
+                } else {
+                    (Box::new(NoColor::new(f)), None)
+                }
+            }
+            None => (Box::new(stdout()), terminal_width()),
         };
+        let out = SharedOutput(Arc::new(Mutex::new(out)));
+        Self {
+            terse_width,
+            truncate_in,
+            shared_out: Some(out.clone()),
+            ..Self::with_output(args, tests, Box::new(out), None)
+        }
+    }
+
+    /// Returns a handle to flush the output from another thread. Output to a
+    /// writer passed to `with_writer` cannot be flushed like this.
+    #[cfg_attr(not(all(unix, feature = "sigterm")), allow(dead_code))]
+    pub(crate) fn shared_output(&self) -> Option<SharedOutput> {
+        self.shared_out.clone()
+    }
+
+    /// Creates a new printer that writes to the given writer instead of
+    /// stdout or the logfile. As we cannot know whether the writer is a
+    /// terminal, it is only colorized with `--color=always`.
+    pub(crate) fn with_writer(
+        args: &Arguments,
+        tests: &[Trial],
+        writer: impl Write + 'a,
+    ) -> Self {
+        let color_arg = args.color.unwrap_or(ColorSetting::Auto);
+        Self::with_output(args, tests, Self::wrap_writer(writer, color_arg), None)
+    }
+
+    /// Creates a new printer that sends all events to the given reporter
+    /// (see `run_with_reporter`) and prints nothing, not even to stderr.
+    pub(crate) fn with_reporter(
+        args: &Arguments,
+        tests: &[Trial],
+        reporter: impl Reporter + 'a,
+    ) -> Self {
+        let out = Box::new(NoColor::new(io::sink()));
+        Self::with_output(args, tests, out, Some(Box::new(reporter)))
+    }
 
+    fn wrap_writer(writer: impl Write + 'a, color_arg: ColorSetting) -> Box<dyn WriteColor + 'a> {
+        if color_arg == ColorSetting::Always {
+            Box::new(Ansi::new(writer))
+        } else {
+            Box::new(NoColor::new(writer))
+        }
+    }
+
+    fn with_output(
+        args: &Arguments,
+        tests: &[Trial],
+        out: Box<dyn WriteColor + 'a>,
+        reporter: Option<Box<dyn Reporter + 'a>>,
+    ) -> Self {
         // Determine correct format
         let format = if args.quiet {
             FormatSetting::Terse
@@ -52,139 +203,626 @@ impl Printer {
             args.format.unwrap_or(FormatSetting::Pretty)
         };
 
+        let mut warnings = Vec::new();
+        if args.strict_width {
+            warnings.extend(
+                tests.iter()
+                    .filter(|test| has_ambiguous_width(&test.info.name))
+                    .map(|test| format!(
+                        "test name {:?} contains characters of ambiguous or zero width and \
+                            might be misaligned",
+                        test.info.name,
+                    )),
+            );
+        }
+        let report_socket = args.report_socket.as_ref().and_then(|addr| {
+            match TcpStream::connect(addr.as_str()) {
+                Ok(socket) => Some(socket),
+                Err(e) => {
+                    warnings.push(format!("failed to connect to report socket {}: {}", addr, e));
+                    None
+                }
+            }
+        });
+
+        let aggregate_fd = args.aggregate_fd.and_then(|fd| match open_fd(fd) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                warnings.push(format!("failed to open aggregate fd {}: {}", fd, e));
+                None
+            }
+        });
+
+        let mut printer = Self {
+            out,
+            shared_out: None,
+            format,
+            name_width: 0,
+            kind_width: 0,
+            align: !args.no_align,
+            minimal: args.minimal_output,
+            silent: args.silent,
+            tap_number: 0,
+            terse_width: None,
+            terse_column: 0,
+            terse_flushed_at: Instant::now(),
+            num_finished: 0,
+            num_tests: 0,
+            noun: args.noun.clone()
+                .unwrap_or_else(|| ("test".to_owned(), "tests".to_owned())),
+            max_message_lines: args.max_message_lines,
+            max_message_bytes: args.max_message_bytes,
+            truncate_in: TruncateIn::Output,
+            slow_threshold: args.slow_threshold,
+            rerun_flags: rerun_flags(args),
+            include_host_info: args.include_host_info,
+            report_socket,
+            aggregate_fd,
+            reporter,
+            junit_results: Vec::new(),
+        };
+        printer.fit_columns(tests);
+        for warning in warnings {
+            printer.print_warning(&warning);
+        }
+        printer
+    }
+
+    /// Notifies the reporter that a test started running.
+    pub(crate) fn test_started(&mut self, info: &TestInfo) {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.test_started(&info.name, &info.kind);
+        }
+    }
+
+    /// Notifies the reporter that a test finished.
+    pub(crate) fn test_finished(&mut self, result: &TrialResult) {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.test_finished(result);
+        }
+        if self.format == FormatSetting::Junit {
+            self.junit_results.push(result.clone());
+        }
+    }
+
+    /// Adjusts the width of the kind and name columns to the given tests.
+    pub(crate) fn fit_columns(&mut self, tests: &[Trial]) {
+        if !self.align {
+            return;
+        }
+
         // Determine max test name length to do nice formatting later.
         //
-        // Unicode is hard and there is no way we can properly align/pad the
-        // test names and outcomes. Counting the number of code points is just
-        // a cheap way that works in most cases. Usually, these names are
-        // ASCII.
-        let name_width = tests.iter()
-            .map(|test| test.info.name.chars().count())
+        // The display width (as determined by `unicode-width`) is correct for
+        // most names, including CJK ones. Some characters have an ambiguous
+        // width which depends on the terminal, `--strict-width` warns about
+        // those.
+        self.name_width = tests.iter()
+            .map(|test| test.info.name.width())
             .max()
             .unwrap_or(0);
 
-        let kind_width = tests.iter()
+        self.kind_width = tests.iter()
             .map(|test| {
                 if test.info.kind.is_empty() {
                     0
                 } else {
                     // The two braces [] and one space
-                    test.info.kind.chars().count() + 3
+                    test.info.kind.width() + 3
                 }
             })
             .max()
             .unwrap_or(0);
+    }
 
-        Self {
-            out,
-            format,
-            name_width,
-            kind_width,
+    /// Sends the given JSON event to the report socket and the aggregate fd,
+    /// if there are any. If that fails, a warning is printed and no further
+    /// events are sent there.
+    fn report(&mut self, event: impl FnOnce() -> String) {
+        if self.report_socket.is_none() && self.aggregate_fd.is_none() {
+            return;
+        }
+
+        let event = event();
+        if let Some(socket) = &mut self.report_socket {
+            if let Err(e) = writeln!(socket, "{}", event) {
+                self.report_socket = None;
+                self.print_warning(&format!("failed to write to report socket: {}", e));
+            }
+        }
+        if let Some(fd) = &mut self.aggregate_fd {
+            if let Err(e) = writeln!(&**fd, "{}", event) {
+                self.aggregate_fd = None;
+                self.print_warning(&format!("failed to write to aggregate fd: {}", e));
+            }
+        }
+    }
+
+    /// Prints a warning on its own line, or passes it to the reporter.
+    pub(crate) fn print_warning(&mut self, msg: &str) {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.warning(msg);
+            return;
+        }
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                let mut color = ColorSpec::new();
+                color.set_fg(Some(Color::Yellow)).set_bold(true);
+                self.out.set_color(&color).unwrap();
+                write!(self.out, "warning").unwrap();
+                self.out.reset().unwrap();
+                writeln!(self.out, ": {}", msg).unwrap();
+            }
+            FormatSetting::Tap => writeln!(self.out, "# warning: {}", msg).unwrap(),
+            FormatSetting::Json | FormatSetting::Junit => {
+                // Writing anything else than JSON to the output would confuse
+                // tools parsing it.
+                eprintln!("warning: {}", msg);
+            }
+        }
+    }
+
+    /// Prints the given bytes (e.g. output of a test) as they are.
+    pub(crate) fn print_raw(&mut self, bytes: &[u8]) {
+        if self.reporter.is_some() {
+            return;
+        }
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                self.out.write_all(bytes).unwrap();
+                self.out.flush().unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Tap | FormatSetting::Junit => {
+                std::io::stderr().write_all(bytes).unwrap();
+            }
+        }
+    }
+
+    /// Prints the randomly chosen seed used to shuffle the tests, so that the
+    /// order can be reproduced.
+    pub(crate) fn print_shuffle_seed(&mut self, seed: u64) {
+        // A reporter gets the seed from `Conclusion::run_info`.
+        if self.reporter.is_some() {
+            return;
+        }
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                writeln!(self.out, "-- using shuffle seed {}", seed).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Junit => {
+                eprintln!("-- using shuffle seed {}", seed);
+            }
+            FormatSetting::Tap => writeln!(self.out, "# using shuffle seed {}", seed).unwrap(),
         }
     }
 
     /// Prints the first line "running 3 tests".
     pub(crate) fn print_title(&mut self, num_tests: u64) {
+        let include_host_info = self.include_host_info;
+        self.num_tests = num_tests;
+        if let Some(reporter) = &mut self.reporter {
+            reporter.suite_started(num_tests);
+        }
+        self.report(|| json::suite_started(num_tests, include_host_info));
         match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse if self.minimal || self.silent => {}
             FormatSetting::Pretty | FormatSetting::Terse => {
-                let plural_s = if num_tests == 1 { "" } else { "s" };
+                let noun = if num_tests == 1 { &self.noun.0 } else { &self.noun.1 };
 
                 writeln!(self.out).unwrap();
-                writeln!(self.out, "running {} test{}", num_tests, plural_s).unwrap();
+                writeln!(self.out, "running {} {}", num_tests, noun).unwrap();
             }
+            FormatSetting::Json => {
+                let event = json::suite_started(num_tests, include_host_info);
+                writeln!(self.out, "{}", event).unwrap();
+            }
+            FormatSetting::Tap => {
+                writeln!(self.out, "TAP version 13").unwrap();
+                writeln!(self.out, "1..{}", num_tests).unwrap();
+            }
+            FormatSetting::Junit => {}
         }
     }
 
+    /// Returns `true` if the start of a test is announced with its own event,
+    /// i.e. if `print_test` should be called as soon as a test starts (even
+    /// when tests run in parallel).
+    pub(crate) fn announces_test_start(&self) -> bool {
+        self.format == FormatSetting::Json
+    }
+
     /// Prints the text announcing the test (e.g. "test foo::bar ... "). Prints
     /// nothing in terse mode.
     pub(crate) fn print_test(&mut self, info: &TestInfo) {
+        self.report(|| json::test_started(info));
         let TestInfo { name, kind, .. } = info;
         match self.format {
+            FormatSetting::Pretty if self.silent => {}
             FormatSetting::Pretty => {
-                let kind = if kind.is_empty() {
-                    format!("")
-                } else {
-                    format!("[{}] ", kind)
-                };
+                let tag = (!kind.is_empty()).then(|| format!("[{}]", kind));
+                let kind_width = tag.as_ref().map_or(0, |tag| tag.width() + 1);
 
-                write!(
-                    self.out,
-                    "test {: <2$}{: <3$} ... ",
-                    kind,
-                    name,
-                    self.kind_width,
-                    self.name_width,
-                ).unwrap();
+                // Padded by hand, as `format!` counts code points.
+                let kind_padding = self.kind_width.saturating_sub(kind_width);
+                let name_padding = self.name_width.saturating_sub(name.width());
+                write!(self.out, "{} ", self.noun.0).unwrap();
+                if let Some(tag) = tag {
+                    let mut color = ColorSpec::new();
+                    color.set_fg(Some(Color::Blue)).set_dimmed(true);
+                    self.out.set_color(&color).unwrap();
+                    write!(self.out, "{}", tag).unwrap();
+                    self.out.reset().unwrap();
+                    write!(self.out, " ").unwrap();
+                }
+                write!(self.out, "{:kind_padding$}{}{:name_padding$} ... ", "", name, "").unwrap();
                 self.out.flush().unwrap();
             }
-            FormatSetting::Terse => {
+            FormatSetting::Terse | FormatSetting::Tap | FormatSetting::Junit => {
                 // In terse mode, nothing is printed before the job. Only
-                // `print_single_outcome` prints one character.
+                // `print_single_outcome` prints one character. In TAP, the
+                // whole line is printed after the test finished. JUnit is
+                // only printed at the end.
+            }
+            FormatSetting::Json => {
+                writeln!(self.out, "{}", json::test_started(info)).unwrap();
+                self.out.flush().unwrap();
             }
         }
     }
 
+    /// Prints a test selected by `--dry-run` instead of running it. Ignored
+    /// tests are printed as such, as they would not run either.
+    pub(crate) fn print_dry_run(&mut self, info: &TestInfo, ignored: bool) {
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse if self.silent => {}
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                // Terse mode uses the pretty lines as well, as one character
+                // per test would not show which tests were selected.
+                let format = mem::replace(&mut self.format, FormatSetting::Pretty);
+                self.print_test(info);
+                self.format = format;
+                if ignored {
+                    self.print_outcome_pretty(&Outcome::Ignored);
+                    self.print_ignore_reason(info, &Outcome::Ignored);
+                    writeln!(self.out).unwrap();
+                } else {
+                    writeln!(self.out, "(dry-run)").unwrap();
+                }
+            }
+            FormatSetting::Tap => self.print_tap_line(info, &Outcome::Ignored),
+            FormatSetting::Json | FormatSetting::Junit => {}
+        }
+    }
+
     /// Prints the outcome of a single tests. `ok` or `FAILED` in pretty mode
     /// and `.` or `F` in terse mode.
-    pub(crate) fn print_single_outcome(&mut self, outcome: &Outcome) {
+    pub(crate) fn print_single_outcome(
+        &mut self,
+        info: &TestInfo,
+        outcome: &Outcome,
+        duration: Duration,
+        retries: u32,
+    ) {
+        self.report(|| json::test_finished(info, outcome));
         match self.format {
+            FormatSetting::Pretty if self.silent => {}
             FormatSetting::Pretty => {
                 self.print_outcome_pretty(outcome);
+                self.print_ignore_reason(info, outcome);
+                if retries > 0 {
+                    let noun = if retries == 1 { "retry" } else { "retries" };
+                    write!(self.out, " (after {} {})", retries, noun).unwrap();
+                }
+                if self.is_slow(duration) {
+                    let mut color = ColorSpec::new();
+                    color.set_fg(Some(Color::Yellow)).set_bold(true);
+                    self.out.set_color(&color).unwrap();
+                    write!(self.out, " <{:.3}s> (slow)", duration.as_secs_f64()).unwrap();
+                    self.out.reset().unwrap();
+                }
                 writeln!(self.out).unwrap();
+                self.out.flush().unwrap();
             }
             FormatSetting::Terse => {
                 let c = match outcome {
-                    Outcome::Passed => '.',
+                    Outcome::Passed { .. } => '.',
                     Outcome::Failed { .. } => 'F',
                     Outcome::Ignored => 'i',
-                    Outcome::Measured { .. } => {
-                        // Benchmark are never printed in terse mode... for
-                        // some reason.
-                        self.print_outcome_pretty(outcome);
-                        writeln!(self.out).unwrap();
-                        return;
-                    }
+                    Outcome::Measured { .. } => 'b',
+                    Outcome::NotRun => 'n',
                 };
 
                 self.out.set_color(&color_of_outcome(outcome)).unwrap();
                 write!(self.out, "{}", c).unwrap();
                 self.out.reset().unwrap();
+
+                self.num_finished += 1;
+                self.terse_column += 1;
+                if let Some(width) = self.terse_width {
+                    // The counter takes at most this many columns.
+                    let counter_width = 2 * self.num_tests.to_string().len() + 2;
+                    if self.terse_column >= width.saturating_sub(counter_width).max(1) {
+                        writeln!(self.out, " {}/{}", self.num_finished, self.num_tests).unwrap();
+                        self.terse_column = 0;
+                    }
+                }
+                if self.terse_flushed_at.elapsed() >= TERSE_FLUSH_INTERVAL {
+                    self.out.flush().unwrap();
+                    self.terse_flushed_at = Instant::now();
+                }
             }
+            FormatSetting::Json => {
+                // Flushed right away so that consumers see each event as soon
+                // as the test finished.
+                writeln!(self.out, "{}", json::test_finished(info, outcome)).unwrap();
+                self.out.flush().unwrap();
+            }
+            FormatSetting::Tap => {
+                self.print_tap_line(info, outcome);
+                self.out.flush().unwrap();
+            }
+            FormatSetting::Junit => {}
+        }
+    }
+
+    /// Prints `, reason` after the outcome of an ignored test that has a
+    /// reason (see `Trial::with_ignore_reason`), like libtest does.
+    fn print_ignore_reason(&mut self, info: &TestInfo, outcome: &Outcome) {
+        if let (Outcome::Ignored, Some(reason)) = (outcome, &info.ignore_reason) {
+            write!(self.out, ", {}", reason).unwrap();
+        }
+    }
+
+    /// Prints the `ok`/`not ok` line of a test in TAP, followed by the
+    /// failure message (if any) in a YAML block.
+    fn print_tap_line(&mut self, info: &TestInfo, outcome: &Outcome) {
+        self.tap_number += 1;
+        let status = match outcome {
+            Outcome::Failed(_) => "not ok",
+            _ => "ok",
+        };
+        // `#` starts a directive, so it has to be escaped in the description.
+        let name = info.name.replace('\\', "\\\\").replace('#', "\\#");
+        write!(self.out, "{} {} - {}", status, self.tap_number, name).unwrap();
+        match outcome {
+            Outcome::Ignored => match &info.ignore_reason {
+                Some(reason) => write!(self.out, " # SKIP {}", reason).unwrap(),
+                None => write!(self.out, " # SKIP").unwrap(),
+            },
+            Outcome::NotRun => write!(self.out, " # SKIP not run").unwrap(),
+            Outcome::Measured(Measurement { avg, variance }) => write!(
+                self.out,
+                " # {} ns/iter (+/- {})",
+                fmt_with_thousand_sep(*avg),
+                fmt_with_thousand_sep(*variance),
+            ).unwrap(),
+            _ => {}
         }
+        writeln!(self.out).unwrap();
+
+        if let Outcome::Failed(Failed { msg: Some(msg), .. }) = outcome {
+            writeln!(self.out, "  ---").unwrap();
+            writeln!(self.out, "  message: |").unwrap();
+            for line in msg.lines() {
+                writeln!(self.out, "    {}", line).unwrap();
+            }
+            writeln!(self.out, "  ...").unwrap();
+        }
+        self.out.flush().unwrap();
     }
 
     /// Prints the summary line after all tests have been executed.
     pub(crate) fn print_summary(&mut self, conclusion: &Conclusion, execution_time: Duration) {
+        self.report(|| json::suite_finished(conclusion, execution_time));
+        if let Some(reporter) = &mut self.reporter {
+            reporter.suite_finished(conclusion);
+        }
         match self.format {
             FormatSetting::Pretty | FormatSetting::Terse => {
                 let outcome = if conclusion.has_failed() {
-                    Outcome::Failed(Failed { msg: None })
+                    Outcome::Failed(Failed::without_message())
                 } else {
-                    Outcome::Passed
+                    Outcome::Passed { assertions: 0 }
                 };
 
-                writeln!(self.out).unwrap();
-                write!(self.out, "test result: ").unwrap();
+                // In terse mode, this also ends the line of outcome characters.
+                if !self.minimal || self.terse_column > 0 {
+                    writeln!(self.out).unwrap();
+                }
+                write!(self.out, "{} result: ", self.noun.0).unwrap();
                 self.print_outcome_pretty(&outcome);
-                writeln!(
+                write!(
                     self.out,
-                    ". {} passed; {} failed; {} ignored; {} measured; \
-                        {} filtered out; finished in {:.2}s",
+                    ". {} passed; {} failed; {} ignored; {} measured; {} filtered out; ",
                     conclusion.num_passed,
                     conclusion.num_failed,
                     conclusion.num_ignored,
                     conclusion.num_measured,
                     conclusion.num_filtered_out,
-                    execution_time.as_secs_f64()
                 ).unwrap();
+                if conclusion.num_assertions > 0 {
+                    write!(self.out, "{} assertions; ", conclusion.num_assertions).unwrap();
+                }
+                if conclusion.num_slow > 0 {
+                    write!(self.out, "{} slow; ", conclusion.num_slow).unwrap();
+                }
+                if conclusion.num_not_run > 0 {
+                    write!(self.out, "{} not run; ", conclusion.num_not_run).unwrap();
+                }
+                writeln!(self.out, "finished in {:.2}s", execution_time.as_secs_f64()).unwrap();
+                if !self.minimal {
+                    writeln!(self.out).unwrap();
+                }
+            }
+            FormatSetting::Json => {
+                let event = json::suite_finished(conclusion, execution_time);
+                writeln!(self.out, "{}", event).unwrap();
+            }
+            FormatSetting::Tap => {
+                write!(
+                    self.out,
+                    "# {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+                    conclusion.num_passed,
+                    conclusion.num_failed,
+                    conclusion.num_ignored,
+                    conclusion.num_measured,
+                    conclusion.num_filtered_out,
+                ).unwrap();
+                if conclusion.num_not_run > 0 {
+                    write!(self.out, "; {} not run", conclusion.num_not_run).unwrap();
+                }
                 writeln!(self.out).unwrap();
             }
+            FormatSetting::Junit => {
+                let results = mem::take(&mut self.junit_results);
+                let xml = junit::render(&junit::suite_name(), &results, conclusion, execution_time);
+                write!(self.out, "{}", xml).unwrap();
+            }
         }
     }
 
+    /// Prints the counts of each kind after the summary line. Used if
+    /// `--summary-by-kind` is set and there is more than one kind.
+    pub(crate) fn print_kind_summary(&mut self, by_kind: &BTreeMap<&str, KindCounts>) {
+        if !matches!(self.format, FormatSetting::Pretty | FormatSetting::Terse) {
+            return;
+        }
+
+        let label = |kind: &str| {
+            if kind.is_empty() { "(no kind)".to_owned() } else { format!("[{}]", kind) }
+        };
+        let width = by_kind.keys().map(|kind| label(kind).width()).max().unwrap_or(0);
+
+        writeln!(self.out, "summary by kind:").unwrap();
+        for (kind, counts) in by_kind {
+            let label = label(kind);
+            let padding = width - label.width();
+            writeln!(
+                self.out,
+                "    {}{:padding$} {} passed; {} failed; {} ignored; {} measured",
+                label,
+                "",
+                counts.passed,
+                counts.failed,
+                counts.ignored,
+                counts.measured,
+            ).unwrap();
+        }
+        writeln!(self.out).unwrap();
+    }
+
+    /// Prints the effective configuration. Used if `--dump-config` is set.
+    pub(crate) fn print_config(&mut self, toml: &str) {
+        write!(self.out, "{}", toml).unwrap();
+    }
+
     /// Prints a list of all tests. Used if `--list` is set.
-    pub(crate) fn print_list(&mut self, tests: &[Trial], ignored: bool) {
-        Self::write_list(tests, ignored, &mut self.out).unwrap();
+    pub(crate) fn print_list(&mut self, tests: &[Trial], args: &Arguments) {
+        let ignored = args.ignored;
+        if args.json_array {
+            Self::write_json_list(tests, ignored, &mut self.out).unwrap();
+        } else if args.verbose_list {
+            self.print_verbose_list(tests, ignored);
+        } else if args.names_only {
+            for test in tests.iter().filter(|test| !ignored || test.info.is_ignored) {
+                writeln!(self.out, "{}", test.info.name).unwrap();
+            }
+        } else if self.format == FormatSetting::Json {
+            // One event per line, like all other JSON output.
+            for test in tests.iter().filter(|test| !ignored || test.info.is_ignored) {
+                writeln!(self.out, "{}", json::list_entry(&test.info)).unwrap();
+            }
+        } else {
+            Self::write_list(tests, ignored, &mut self.out).unwrap();
+        }
+    }
+
+    /// Prints one line per test with aligned columns for the name, kind, type
+    /// and ignore status, followed by the number of tests and benchmarks.
+    /// Used if `--verbose-list` is set.
+    fn print_verbose_list(&mut self, tests: &[Trial], ignored: bool) {
+        let tests = tests.iter()
+            .filter(|test| !ignored || test.info.is_ignored)
+            .collect::<Vec<_>>();
+        let tag = |test: &Trial| {
+            if test.info.kind.is_empty() {
+                String::new()
+            } else {
+                format!("[{}]", test.info.kind)
+            }
+        };
+        let name_width = tests.iter().map(|test| test.info.name.width()).max().unwrap_or(0);
+        let kind_width = tests.iter().map(|test| tag(test).width()).max().unwrap_or(0);
+
+        for test in &tests {
+            // Padded by hand, as `format!` counts code points.
+            let mut line = test.info.name.clone();
+            line.extend(iter::repeat(' ').take(name_width - test.info.name.width() + 2));
+            if kind_width > 0 {
+                let tag = tag(test);
+                line.push_str(&tag);
+                line.extend(iter::repeat(' ').take(kind_width - tag.width() + 2));
+            }
+            line.push_str(if test.info.is_bench { "bench" } else { "test " });
+            if test.info.is_ignored {
+                match &test.info.ignore_reason {
+                    Some(reason) => line.push_str(&format!("  (ignored: {})", reason)),
+                    None => line.push_str("  (ignored)"),
+                }
+            }
+            writeln!(self.out, "{}", line.trim_end()).unwrap();
+        }
+
+        let num_benches = tests.iter().filter(|test| test.info.is_bench).count();
+        let num_tests = tests.len() - num_benches;
+        writeln!(self.out).unwrap();
+        writeln!(
+            self.out,
+            "{} {}, {} {}",
+            num_tests,
+            if num_tests == 1 { &self.noun.0 } else { &self.noun.1 },
+            num_benches,
+            if num_benches == 1 { "benchmark" } else { "benchmarks" },
+        ).unwrap();
+    }
+
+    /// Prints the tests added and removed compared to the baseline list.
+    /// Used if `--list` and `--baseline` are set.
+    pub(crate) fn print_list_diff(&mut self, added: &[&str], removed: &[&str]) {
+        for (sign, lines, color) in [('+', added, Color::Green), ('-', removed, Color::Red)] {
+            for line in lines {
+                self.out.set_color(ColorSpec::new().set_fg(Some(color))).unwrap();
+                write!(self.out, "{} {}", sign, line).unwrap();
+                self.out.reset().unwrap();
+                writeln!(self.out).unwrap();
+            }
+        }
+
+        writeln!(
+            self.out,
+            "{} added, {} removed compared to the baseline",
+            added.len(),
+            removed.len(),
+        ).unwrap();
+    }
+
+    /// Like `write_list`, but writes a single JSON array with one object per
+    /// test (`--json-array`).
+    pub(crate) fn write_json_list(
+        tests: &[Trial],
+        ignored: bool,
+        mut out: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        let entries = tests.iter()
+            .filter(|test| !ignored || test.info.is_ignored)
+            .map(|test| json::list_entry(&test.info))
+            .collect::<Vec<_>>();
+
+        if entries.is_empty() {
+            writeln!(out, "[]")
+        } else {
+            writeln!(out, "[\n  {}\n]", entries.join(",\n  "))
+        }
     }
 
     pub(crate) fn write_list(
@@ -201,7 +839,7 @@ impl Printer {
             }
 
             let kind = if test.info.kind.is_empty() {
-                format!("")
+                String::new()
             } else {
                 format!("[{}] ", test.info.kind)
             };
@@ -220,16 +858,27 @@ impl Printer {
 
     /// Prints a list of failed tests with their messages. This is only called
     /// if there were any failures.
-    pub(crate) fn print_failures(&mut self, fails: &[(TestInfo, Option<String>)]) {
+    pub(crate) fn print_failures(&mut self, fails: &[(TestInfo, Failed)]) {
+        // In JSON, TAP and JUnit mode, the messages are already part of the
+        // output of each failed test.
+        if matches!(self.format, FormatSetting::Json | FormatSetting::Tap | FormatSetting::Junit) {
+            return;
+        }
+
         writeln!(self.out).unwrap();
+        self.terse_column = 0;
         writeln!(self.out, "failures:").unwrap();
         writeln!(self.out).unwrap();
 
         // Print messages of all tests
-        for (test_info, msg) in fails {
-            writeln!(self.out, "---- {} ----", test_info.name).unwrap();
-            if let Some(msg) = msg {
-                writeln!(self.out, "{}", msg).unwrap();
+        for (test_info, failed) in fails {
+            writeln!(self.out, "---- {} stdout ----", test_info.name).unwrap();
+            if let Some(msg) = failed.message() {
+                self.print_truncated_failure_message(msg, failed.mismatched_values());
+            }
+            if let Some(backtrace) = failed.backtrace() {
+                writeln!(self.out, "stack backtrace:").unwrap();
+                write!(self.out, "{}", backtrace).unwrap();
             }
             writeln!(self.out).unwrap();
         }
@@ -240,15 +889,121 @@ impl Printer {
         for (test_info, _) in fails {
             writeln!(self.out, "    {}", test_info.name).unwrap();
         }
+
+        // Print the command to only rerun the failed tests
+        let program = std::env::args_os().next()
+            .map(|program| program.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let names = fails.iter()
+            .take(MAX_RERUN_NAMES)
+            .map(|(test_info, _)| shell_quote(&test_info.name))
+            .collect::<Vec<_>>();
+        writeln!(self.out).unwrap();
+        write!(self.out, "to rerun: {}", shell_quote(&program)).unwrap();
+        for flag in &self.rerun_flags {
+            write!(self.out, " {}", shell_quote(flag)).unwrap();
+        }
+        write!(self.out, " --exact {}", names.join(" ")).unwrap();
+        if fails.len() > MAX_RERUN_NAMES {
+            write!(self.out, " (and {} more)", fails.len() - MAX_RERUN_NAMES).unwrap();
+        }
+        writeln!(self.out).unwrap();
+    }
+
+    /// Prints the message of a failure, truncated on the terminal (see
+    /// `TruncateIn`).
+    fn print_truncated_failure_message(&mut self, msg: &str, mismatch: Option<(&str, &str)>) {
+        let truncated = truncate_message(msg, self.max_message_lines, self.max_message_bytes);
+        if let Cow::Borrowed(_) = truncated {
+            self.print_failure_message(msg, mismatch);
+            return;
+        }
+        match self.truncate_in.clone() {
+            TruncateIn::Output => self.print_failure_message(&truncated, mismatch),
+            TruncateIn::Nothing => self.print_failure_message(msg, mismatch),
+            TruncateIn::TeeFirst(target) => {
+                target.set(TeeTarget::FIRST);
+                self.print_failure_message(&truncated, mismatch);
+                target.set(TeeTarget::SECOND);
+                self.print_failure_message(msg, mismatch);
+                target.set(TeeTarget::BOTH);
+            }
+        }
     }
 
-    /// Prints a colored 'ok'/'FAILED'/'ignored'/'bench'.
+    /// Prints the message of a failure. The values of a mismatch (see
+    /// [`Failed::mismatch`]) are colored, unless the message was changed so
+    /// that it doesn't end with them anymore.
+    fn print_failure_message(&mut self, msg: &str, mismatch: Option<(&str, &str)>) {
+        let split = mismatch.and_then(|(left, right)| {
+            let prefix = msg.strip_suffix(&mismatch_lines(left, right))?;
+            Some((prefix, left, right))
+        });
+        let (prefix, left, right) = match split {
+            Some(split) => split,
+            None => {
+                writeln!(self.out, "{}", msg).unwrap();
+                return;
+            }
+        };
+
+        writeln!(self.out, "{}", prefix).unwrap();
+        for (label, value, color) in [(" left", left, Color::Red), ("right", right, Color::Green)] {
+            write!(self.out, " {}: ", label).unwrap();
+            self.out.set_color(ColorSpec::new().set_fg(Some(color))).unwrap();
+            write!(self.out, "{}", value).unwrap();
+            self.out.reset().unwrap();
+            writeln!(self.out).unwrap();
+        }
+    }
+
+    /// Returns whether a test that ran for `duration` counts as slow.
+    pub(crate) fn is_slow(&self, duration: Duration) -> bool {
+        self.slow_threshold.map_or(false, |threshold| duration > threshold)
+    }
+
+    /// Prints a table of all benchmark results, sorted by name. This is only
+    /// called if any benchmark was measured.
+    pub(crate) fn print_bench_table(&mut self, benches: &[(&str, &Measurement)]) {
+        // In JSON and TAP mode, the measurements are already part of the
+        // output of each benchmark. JUnit has no place for them.
+        if matches!(self.format, FormatSetting::Json | FormatSetting::Tap | FormatSetting::Junit) {
+            return;
+        }
+
+        let mut rows = benches.iter()
+            .map(|(name, m)| {
+                (*name, fmt_with_thousand_sep(m.avg), fmt_with_thousand_sep(m.variance))
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| a.0.cmp(b.0));
+        let name_width = rows.iter().map(|row| row.0.width()).max().unwrap_or(0);
+        let avg_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0);
+
+        writeln!(self.out).unwrap();
+        self.terse_column = 0;
+        writeln!(self.out, "benchmarks:").unwrap();
+        for (name, avg, variance) in rows {
+            let padding = name_width - name.width();
+            writeln!(
+                self.out,
+                "    {}{:padding$}  {:>avg_width$} ns/iter (+/- {})",
+                name,
+                "",
+                avg,
+                variance,
+            ).unwrap();
+        }
+    }
+
+    /// Prints a colored 'ok'/'FAILED'/'ignored'/'bench'/'not run'.
     fn print_outcome_pretty(&mut self, outcome: &Outcome) {
         let s = match outcome {
-            Outcome::Passed => "ok",
+            Outcome::Passed { .. } => "ok",
             Outcome::Failed { .. } => "FAILED",
             Outcome::Ignored => "ignored",
             Outcome::Measured { .. } => "bench",
+            Outcome::NotRun => "not run",
         };
 
         self.out.set_color(&color_of_outcome(outcome)).unwrap();
@@ -266,6 +1021,300 @@ impl Printer {
     }
 }
 
+/// How often the output is flushed in terse mode. In all other formats, it
+/// is flushed after every test, so that the progress is visible right away
+/// even if stdout is not a terminal (e.g. in CI logs).
+const TERSE_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Maximum number of names in the rerun hint printed after the failures.
+const MAX_RERUN_NAMES: usize = 10;
+
+/// Returns the flags of `args` that select which tests run and therefore
+/// have to be repeated in the rerun hint: `--ignored`, `--include-ignored`,
+/// `--test`, `--bench`, `--exclude-should-panic`, `--kind`, `--tag`,
+/// `--all-tags` and `--exclude-tag`. Name filters, `--skip` and
+/// `--partition` are not repeated, as the hint names the failed tests
+/// exactly.
+fn rerun_flags(args: &Arguments) -> Vec<String> {
+    let switches = [
+        (args.ignored, "--ignored"),
+        (args.include_ignored, "--include-ignored"),
+        (args.test, "--test"),
+        (args.bench, "--bench"),
+        (args.exclude_should_panic, "--exclude-should-panic"),
+        (args.all_tags, "--all-tags"),
+    ];
+    let mut flags = switches.iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| flag.to_string())
+        .collect::<Vec<_>>();
+    let options = [
+        ("--kind", &args.kinds),
+        ("--tag", &args.tags),
+        ("--exclude-tag", &args.exclude_tags),
+    ];
+    for (flag, values) in options {
+        for value in values {
+            flags.push(format!("{}={}", flag, value));
+        }
+    }
+    flags
+}
+
+/// Quotes `s` for a POSIX shell, unless it only contains characters that
+/// need no quoting.
+fn shell_quote(s: &str) -> Cow<'_, str> {
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "_-:./=,+@%".contains(c);
+    if !s.is_empty() && s.chars().all(is_plain) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(format!("'{}'", s.replace('\'', "'\\''")))
+    }
+}
+
+/// Truncates a failure message to `max_lines` lines and `max_bytes` bytes
+/// (`--max-message-lines` and `--max-message-bytes`), followed by a line
+/// saying how much was cut off.
+fn truncate_message(msg: &str, max_lines: Option<usize>, max_bytes: Option<usize>) -> Cow<'_, str> {
+    let mut end = msg.len();
+    let mut cut = None;
+    if let Some(max) = max_lines {
+        let num_lines = msg.lines().count();
+        if num_lines > max {
+            end = msg.split_inclusive('\n').take(max).map(str::len).sum();
+            cut = Some((num_lines - max, "lines"));
+        }
+    }
+    if let Some(max) = max_bytes {
+        if end > max {
+            end = max;
+            while !msg.is_char_boundary(end) {
+                end -= 1;
+            }
+            cut = Some((msg.len() - end, "bytes"));
+        }
+    }
+
+    let (num, unit) = match cut {
+        Some(cut) => cut,
+        None => return Cow::Borrowed(msg),
+    };
+    let kept = msg[..end].strip_suffix('\n').unwrap_or(&msg[..end]);
+    let separator = if kept.is_empty() { "" } else { "\n" };
+    Cow::Owned(format!("{}{}... (truncated, {} more {})", kept, separator, num, unit))
+}
+
+/// Silently drops all output once the reader closed the pipe (e.g. when
+/// stdout is piped into `head`), so that the tests still run to completion
+/// instead of panicking on the next write. Other errors are passed through.
+struct IgnoreBrokenPipe<W> {
+    inner: W,
+    closed: bool,
+}
+
+impl<W> IgnoreBrokenPipe<W> {
+    fn guard<T>(&mut self, ok: T, f: impl FnOnce(&mut W) -> io::Result<T>) -> io::Result<T> {
+        if self.closed {
+            return Ok(ok);
+        }
+        match f(&mut self.inner) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                self.closed = true;
+                Ok(ok)
+            }
+            result => result,
+        }
+    }
+}
+
+impl<W: Write> Write for IgnoreBrokenPipe<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.guard(buf.len(), |inner| inner.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.guard((), |inner| inner.flush())
+    }
+}
+
+impl<W: WriteColor> WriteColor for IgnoreBrokenPipe<W> {
+    fn supports_color(&self) -> bool {
+        self.inner.supports_color()
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.guard((), |inner| inner.set_color(spec))
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        self.guard((), |inner| inner.reset())
+    }
+}
+
+/// Output that is shared with another thread, so that it can be flushed from
+/// there (see `Printer::shared_output`).
+#[derive(Clone)]
+pub(crate) struct SharedOutput(Arc<Mutex<Box<dyn WriteColor + Send>>>);
+
+impl SharedOutput {
+    fn lock(&self) -> MutexGuard<'_, Box<dyn WriteColor + Send>> {
+        // A panic while writing does not leave the writer in a broken state.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+impl WriteColor for SharedOutput {
+    fn supports_color(&self) -> bool {
+        self.lock().supports_color()
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.lock().set_color(spec)
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        self.lock().reset()
+    }
+}
+
+/// Where messages in the list of failures are truncated: only on the
+/// terminal, so that the logfile always contains the whole message.
+#[derive(Clone)]
+enum TruncateIn {
+    /// The output is stdout or a writer replacing it.
+    Output,
+
+    /// The output is the logfile.
+    Nothing,
+
+    /// The output is a `Tee` to stdout (the first writer) and the logfile.
+    TeeFirst(TeeTarget),
+}
+
+/// Selects which writers of a `Tee` receive the output.
+#[derive(Clone, Default)]
+struct TeeTarget(Arc<AtomicU8>);
+
+impl TeeTarget {
+    const BOTH: u8 = 0;
+    const FIRST: u8 = 1;
+    const SECOND: u8 = 2;
+
+    fn set(&self, target: u8) {
+        self.0.store(target, Ordering::SeqCst);
+    }
+
+    fn get(&self) -> (bool, bool) {
+        match self.0.load(Ordering::SeqCst) {
+            Self::FIRST => (true, false),
+            Self::SECOND => (false, true),
+            _ => (true, true),
+        }
+    }
+}
+
+/// Writes everything to two writers, e.g. stdout and the logfile with
+/// `--tee`, unless the target selects only one of them. Colors are set on
+/// both; writers that do not support colors ignore them.
+struct Tee<A, B>(A, B, TeeTarget);
+
+impl<A: Write, B: Write> Write for Tee<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (first, second) = self.2.get();
+        if first {
+            self.0.write_all(buf)?;
+        }
+        if second {
+            self.1.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()?;
+        self.1.flush()
+    }
+}
+
+impl<A: WriteColor, B: WriteColor> WriteColor for Tee<A, B> {
+    fn supports_color(&self) -> bool {
+        self.0.supports_color() || self.1.supports_color()
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.0.set_color(spec)?;
+        self.1.set_color(spec)
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        self.0.reset()?;
+        self.1.reset()
+    }
+}
+
+/// Returns the width of the terminal stdout is connected to: the value of the
+/// `COLUMNS` environment variable if it is set, otherwise the width reported
+/// by the terminal. Returns `None` if stdout is not a terminal (e.g. because
+/// it is redirected to a file), even if `COLUMNS` is set.
+fn terminal_width() -> Option<usize> {
+    if !stdout_is_terminal() {
+        return None;
+    }
+    std::env::var("COLUMNS").ok()
+        .and_then(|columns| columns.parse().ok())
+        .or_else(|| terminal_size::terminal_size().map(|(width, _)| width.0 as usize))
+}
+
+/// Returns whether stdout is a terminal, even if its size is unknown.
+#[cfg(unix)]
+fn stdout_is_terminal() -> bool {
+    // SAFETY: `isatty` only queries the descriptor.
+    unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+}
+
+#[cfg(not(unix))]
+fn stdout_is_terminal() -> bool {
+    terminal_size::terminal_size().is_some()
+}
+
+/// Opens the inherited file descriptor given by `--aggregate-fd`, without
+/// taking ownership of it.
+#[cfg(unix)]
+fn open_fd(fd: i32) -> io::Result<ManuallyDrop<File>> {
+    use std::os::unix::io::FromRawFd;
+
+    // SAFETY: `fcntl` only queries the flags of the descriptor, which fails
+    // if it is not open.
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: the descriptor is open, and wrapping the `File` in
+    // `ManuallyDrop` makes sure it is not closed.
+    Ok(ManuallyDrop::new(unsafe { File::from_raw_fd(fd) }))
+}
+
+#[cfg(not(unix))]
+fn open_fd(_fd: i32) -> io::Result<ManuallyDrop<File>> {
+    Err(io::Error::new(io::ErrorKind::Other, "only supported on Unix"))
+}
+
+/// Returns whether the given string contains characters whose display width
+/// is zero or differs between terminals (East Asian Ambiguous characters).
+fn has_ambiguous_width(s: &str) -> bool {
+    s.chars().any(|c| c.width().map_or(true, |w| w == 0) || c.width() != c.width_cjk())
+}
+
 /// Formats the given integer with `,` as thousand separator.
 pub fn fmt_with_thousand_sep(mut v: u64) -> String {
     let mut out = String::new();
@@ -282,9 +1331,9 @@ pub fn fmt_with_thousand_sep(mut v: u64) -> String {
 fn color_of_outcome(outcome: &Outcome) -> ColorSpec {
     let mut out = ColorSpec::new();
     let color = match outcome {
-        Outcome::Passed => Color::Green,
+        Outcome::Passed { .. } => Color::Green,
         Outcome::Failed { .. } => Color::Red,
-        Outcome::Ignored => Color::Yellow,
+        Outcome::Ignored | Outcome::NotRun => Color::Yellow,
         Outcome::Measured { .. } => Color::Cyan,
     };
     out.set_fg(Some(color));
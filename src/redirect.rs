@@ -0,0 +1,115 @@
+//! Definition of the `Redirect`.
+//!
+//! This temporarily redirects the file descriptor of stdout or stderr into a
+//! temporary file, which catches everything written to it, no matter how
+//! (unlike the unstable `std::io::set_output_capture`, which only affects
+//! `print!` and friends). As this affects the whole process, it must only be
+//! used while a single test is running. Only supported on Unix.
+
+use std::io;
+
+
+/// The stream to redirect.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    #[cfg(unix)]
+    fn fd(self) -> i32 {
+        match self {
+            Stream::Stdout => libc::STDOUT_FILENO,
+            Stream::Stderr => libc::STDERR_FILENO,
+        }
+    }
+
+    #[cfg(unix)]
+    fn flush(self) -> io::Result<()> {
+        use std::io::Write;
+
+        match self {
+            Stream::Stdout => io::stdout().flush(),
+            Stream::Stderr => io::stderr().flush(),
+        }
+    }
+}
+
+pub(crate) struct Redirect {
+    #[cfg(unix)]
+    stream: Stream,
+    #[cfg(unix)]
+    file: std::fs::File,
+    #[cfg(unix)]
+    saved_fd: std::os::unix::io::RawFd,
+}
+
+impl Redirect {
+    /// Starts redirecting the given stream.
+    #[cfg(unix)]
+    pub(crate) fn start(stream: Stream) -> io::Result<Self> {
+        use std::{
+            fs::{self, OpenOptions},
+            os::unix::io::AsRawFd,
+            sync::atomic::{AtomicU64, Ordering},
+        };
+
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        // The file is deleted right away, so that it does not stay around
+        // even if the process is killed. We keep using it via its descriptor.
+        let path = std::env::temp_dir().join(format!(
+            "libtest-mimic-output-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+        ));
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        fs::remove_file(&path)?;
+
+        stream.flush()?;
+        let saved_fd = check(unsafe { libc::dup(stream.fd()) })?;
+        if let Err(e) = check(unsafe { libc::dup2(file.as_raw_fd(), stream.fd()) }) {
+            unsafe { libc::close(saved_fd) };
+            return Err(e);
+        }
+
+        Ok(Self { stream, file, saved_fd })
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn start(_stream: Stream) -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Other, "redirecting output is only supported on Unix"))
+    }
+
+    /// Stops redirecting and returns everything that was written to the
+    /// stream in the meantime.
+    #[cfg(unix)]
+    pub(crate) fn finish(mut self) -> io::Result<Vec<u8>> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let flushed = self.stream.flush();
+        check(unsafe { libc::dup2(self.saved_fd, self.stream.fd()) })?;
+        unsafe { libc::close(self.saved_fd) };
+        flushed?;
+
+        let mut output = Vec::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_end(&mut output)?;
+        Ok(output)
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn finish(self) -> io::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(unix)]
+fn check(ret: i32) -> io::Result<i32> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
@@ -0,0 +1,54 @@
+//! Definition of the `Reporter` trait, see [`run_with_reporter`][crate::run_with_reporter].
+
+use std::sync::mpsc;
+
+use crate::{Conclusion, TrialResult};
+
+
+/// Receives the events of a run, e.g. to show the progress in a GUI. Pass it
+/// to [`run_with_reporter`][crate::run_with_reporter].
+///
+/// All methods do nothing by default, so only the events of interest need to
+/// be implemented. All methods are called from the thread that started the
+/// run, even if the trials run in parallel.
+pub trait Reporter {
+    /// Called once before the first trial starts, with the number of trials
+    /// that are not filtered out.
+    fn suite_started(&mut self, num_tests: u64) {
+        let _ = num_tests;
+    }
+
+    /// Called when a trial starts running. Not called for ignored trials.
+    fn test_started(&mut self, name: &str, kind: &str) {
+        let _ = (name, kind);
+    }
+
+    /// Called when a trial finished (or was ignored).
+    fn test_finished(&mut self, result: &TrialResult) {
+        let _ = result;
+    }
+
+    /// Called once after all trials finished, with the same conclusion
+    /// returned by the `run_*` function.
+    fn suite_finished(&mut self, conclusion: &Conclusion) {
+        let _ = conclusion;
+    }
+
+    /// Called with every warning that `run` would print, e.g. that a test
+    /// wrote to stdout without `--nocapture` or that there are no tests to
+    /// run.
+    fn warning(&mut self, msg: &str) {
+        let _ = msg;
+    }
+}
+
+/// Sends the result of every trial through a channel, see
+/// [`run_streaming`][crate::run_streaming].
+pub(crate) struct ChannelReporter(pub(crate) mpsc::Sender<TrialResult>);
+
+impl Reporter for ChannelReporter {
+    fn test_finished(&mut self, result: &TrialResult) {
+        // The receiver may have been dropped, which is fine.
+        let _ = self.0.send(result.clone());
+    }
+}
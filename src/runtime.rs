@@ -0,0 +1,115 @@
+//! Running async trials on a tokio runtime (only with the `tokio` feature).
+//!
+//! With [`run_async`][crate::run_async] and more than one test thread, async
+//! trials are spawned onto the runtime, and a semaphore limits how many of
+//! them run at the same time. In all other cases (e.g. with [`run`][crate::run]
+//! or `--test-threads=1`), every async trial is turned into a normal runner
+//! that blocks on its future, so that everything else (capturing, resources,
+//! timeouts, ...) works the same as for sync trials.
+
+use std::{
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{mpsc, Arc},
+    task::{Context, Poll},
+    thread,
+    time::{Duration, Instant},
+};
+
+use tokio::{runtime::Handle, sync::Semaphore};
+
+use crate::{panic_message, Outcome, RunContext, RunControl, WorkerEvent};
+
+
+/// Creates the future of an async trial.
+pub(crate) type AsyncRunner =
+    Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = Outcome> + Send>> + Send>;
+
+/// Turns an async runner into a sync one that blocks on the future, using
+/// `handle` or, if that is `None`, a new single-threaded runtime per trial.
+pub(crate) fn blocking(
+    runner: AsyncRunner,
+    handle: Option<Handle>,
+) -> Box<dyn FnOnce(RunContext) -> Outcome + Send> {
+    Box::new(move |_| match handle {
+        Some(handle) => handle.block_on(runner()),
+        None => tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build tokio runtime")
+            .block_on(runner()),
+    })
+}
+
+/// Like `Trial::with_should_panic` for sync runners: panics while creating
+/// or polling the future are caught and turned into a passing outcome.
+pub(crate) fn expect_panic(runner: AsyncRunner, expected: Option<String>) -> AsyncRunner {
+    Box::new(move || Box::pin(async move {
+        let result = match catch_unwind(AssertUnwindSafe(runner)) {
+            Ok(future) => CatchUnwind(future).await,
+            Err(payload) => Err(payload),
+        };
+        crate::expect_panic(result, expected.as_deref())
+    }))
+}
+
+/// Catches panics while polling the inner future.
+struct CatchUnwind<F>(F);
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.0;
+        match catch_unwind(AssertUnwindSafe(|| Pin::new(inner).poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// Spawns async trials onto a runtime, running at most a fixed number of
+/// them at the same time.
+pub(crate) struct Spawner {
+    handle: Handle,
+    semaphore: Arc<Semaphore>,
+    control: RunControl,
+}
+
+impl Spawner {
+    pub(crate) fn new(handle: Handle, max_concurrent: usize, control: RunControl) -> Self {
+        Self {
+            handle,
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            control,
+        }
+    }
+
+    /// Runs the trial with index `idx`, reporting to `sender` just like the
+    /// worker threads do.
+    pub(crate) fn spawn(&self, idx: usize, runner: AsyncRunner, sender: mpsc::Sender<WorkerEvent>) {
+        let handle = self.handle.clone();
+        let semaphore = self.semaphore.clone();
+        let control = self.control.clone();
+        self.handle.spawn(async move {
+            // The semaphore is never closed, so this cannot fail.
+            let _permit = semaphore.acquire_owned().await;
+            if let Some(outcome) = control.skip_if_aborted() {
+                let _ = sender.send(WorkerEvent::Finished(idx, outcome, Duration::ZERO, 0));
+                return;
+            }
+            let _ = sender.send(WorkerEvent::Started(idx, Instant::now()));
+            let start = Instant::now();
+
+            // The trial is spawned as its own task to catch panics.
+            let outcome = match handle.spawn(async move { runner().await }).await {
+                Ok(outcome) => outcome,
+                Err(e) if e.is_panic() => Outcome::Failed(panic_message(&*e.into_panic()).into()),
+                Err(_) => Outcome::Failed("test was cancelled".into()),
+            };
+            let _ = sender.send(WorkerEvent::Finished(idx, outcome, start.elapsed(), 0));
+        });
+    }
+}
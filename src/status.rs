@@ -0,0 +1,136 @@
+//! Definition of the `StatusFile`.
+//!
+//! If `--exit-status-file` is specified, the final counts of the run are
+//! written to that file. With the `sigterm` feature enabled, the counts
+//! gathered so far are also written if the process is terminated via SIGTERM,
+//! so that a supervisor killing the test binary does not lose all results.
+
+use std::{
+    fs,
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use crate::Conclusion;
+#[cfg(all(unix, feature = "sigterm"))]
+use crate::printer::SharedOutput;
+
+
+#[derive(Clone)]
+pub(crate) struct StatusFile {
+    path: PathBuf,
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    conclusion: Conclusion,
+    complete: bool,
+}
+
+impl StatusFile {
+    pub(crate) fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            state: Arc::new(Mutex::new(State {
+                conclusion: Conclusion::default(),
+                complete: false,
+            })),
+        }
+    }
+
+    /// Remembers the counts gathered so far, to be written in case the run is
+    /// interrupted.
+    pub(crate) fn update(&self, conclusion: &Conclusion) {
+        self.lock().conclusion = conclusion.clone();
+    }
+
+    /// Writes the final conclusion. After this, an interruption does not
+    /// touch the file anymore.
+    pub(crate) fn finish(&self, conclusion: &Conclusion) -> io::Result<()> {
+        let mut state = self.lock();
+        state.conclusion = conclusion.clone();
+        state.complete = true;
+        self.write(&state)
+    }
+
+    /// Writes the partial conclusion, unless the run has already finished.
+    /// This is what the SIGTERM handler calls.
+    #[cfg_attr(not(all(unix, feature = "sigterm")), allow(dead_code))]
+    pub(crate) fn interrupt(&self) -> io::Result<()> {
+        let state = self.lock();
+        if state.complete {
+            return Ok(());
+        }
+        self.write(&state)
+    }
+
+    fn write(&self, state: &State) -> io::Result<()> {
+        let c = &state.conclusion;
+        let json = format!(
+            concat!(
+                r#"{{"complete":{},"passed":{},"failed":{},"ignored":{},"#,
+                r#""measured":{},"filtered_out":{}}}"#,
+            ),
+            state.complete,
+            c.num_passed,
+            c.num_failed,
+            c.num_ignored,
+            c.num_measured,
+            c.num_filtered_out,
+        );
+        fs::write(&self.path, json + "\n")
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // A poisoned lock only means a panic happened while updating the
+        // counts, which are still perfectly usable.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Spawns a thread that, on SIGTERM, writes the partial conclusion,
+    /// flushes `out` and then terminates the process as SIGTERM would
+    /// normally do. The handler is removed when the returned value is
+    /// dropped at the end of the run.
+    #[cfg(all(unix, feature = "sigterm"))]
+    pub(crate) fn install_sigterm_handler(
+        &self,
+        out: Option<SharedOutput>,
+    ) -> io::Result<SigtermHandler> {
+        use std::io::Write;
+        use signal_hook::{consts::SIGTERM, iterator::Signals, low_level::emulate_default_handler};
+
+        let mut signals = Signals::new([SIGTERM])?;
+        let handle = signals.handle();
+        let status = self.clone();
+        let thread = std::thread::spawn(move || {
+            if signals.forever().next().is_some() {
+                let _ = status.interrupt();
+                if let Some(mut out) = out {
+                    let _ = out.flush();
+                }
+                let _ = emulate_default_handler(SIGTERM);
+            }
+        });
+
+        Ok(SigtermHandler { handle, thread: Some(thread) })
+    }
+}
+
+/// Unregisters the SIGTERM handler and stops its thread when dropped, so that
+/// a finished run does not react to signals meant for a later one.
+#[cfg(all(unix, feature = "sigterm"))]
+pub(crate) struct SigtermHandler {
+    handle: signal_hook::iterator::Handle,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(all(unix, feature = "sigterm"))]
+impl Drop for SigtermHandler {
+    fn drop(&mut self) {
+        self.handle.close();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
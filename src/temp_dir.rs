@@ -0,0 +1,58 @@
+//! Definition of `TempDir`, the scratch directory created for tests defined
+//! via `Trial::test_in_temp_dir`.
+
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+
+/// Counter making the directory names unique within this process.
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A freshly created, empty directory that is removed when dropped, unless
+/// it should be kept for debugging (see `keep`).
+pub(crate) struct TempDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempDir {
+    /// Creates a new directory in the system's temporary directory. The name
+    /// contains the process ID, a counter and (a sanitized version of) the
+    /// test name, e.g. `libtest-mimic-1234-7-foo_bar`.
+    pub(crate) fn create(test_name: &str) -> io::Result<Self> {
+        let sanitized = test_name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .take(64)
+            .collect::<String>();
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let name = format!("libtest-mimic-{}-{}-{}", process::id(), count, sanitized);
+        let path = std::env::temp_dir().join(name);
+        fs::create_dir(&path)?;
+
+        Ok(Self { path, keep: false })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Prevents the directory from being removed when this is dropped.
+    pub(crate) fn keep(&mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.keep {
+            // There is not much we can do about errors here, and a left over
+            // directory in the temp dir is not worth failing the test for.
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Definition of the `Timings` used by `--timings` and `--time-budget`.
+//!
+//! The timings file has one line per test, `<seconds> <name>`, describing
+//! how long that test took the last time it ran. Tests whose names contain
+//! line breaks are not recorded.
+
+use std::{collections::BTreeMap, fs, io, path::Path, time::Duration};
+
+use crate::{Outcome, Trial, TrialResult};
+
+
+#[derive(Debug, Default)]
+pub(crate) struct Timings {
+    /// The duration of the test with the given name the last time it ran.
+    durations: BTreeMap<String, Duration>,
+}
+
+impl Timings {
+    /// Loads the timings from the given file. A missing file is treated like
+    /// an empty one, as it is created by the first run.
+    pub(crate) fn load(path: &Path) -> io::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        let durations = content.lines()
+            .filter_map(|line| {
+                let (secs, name) = line.split_once(' ')?;
+                let secs = secs.parse::<f64>().ok().filter(|s| s.is_finite() && *s >= 0.0)?;
+                Some((name.to_owned(), Duration::from_secs_f64(secs)))
+            })
+            .collect();
+        Ok(Self { durations })
+    }
+
+    /// Returns the recorded duration of the test with the given name.
+    pub(crate) fn get(&self, name: &str) -> Option<Duration> {
+        self.durations.get(name).copied()
+    }
+
+    /// Selects the tests to run within the given budget, keeping their order.
+    /// Tests are picked from fastest to slowest, so that as many as possible
+    /// are run. Tests without recorded duration are always selected, so that
+    /// their duration is known next time. Returns the removed tests.
+    pub(crate) fn select(&self, tests: &mut Vec<Trial>, budget: Duration) -> Vec<Trial> {
+        let mut by_duration = tests.iter()
+            .enumerate()
+            .filter_map(|(i, test)| Some((self.get(&test.info.name)?, i)))
+            .collect::<Vec<_>>();
+        by_duration.sort();
+
+        let mut selected = vec![true; tests.len()];
+        let mut total = Duration::ZERO;
+        for (duration, i) in by_duration {
+            if total + duration <= budget {
+                total += duration;
+            } else {
+                selected[i] = false;
+            }
+        }
+
+        let mut removed = Vec::new();
+        let mut kept = Vec::with_capacity(tests.len());
+        for (test, selected) in tests.drain(..).zip(selected) {
+            if selected {
+                kept.push(test);
+            } else {
+                removed.push(test);
+            }
+        }
+        *tests = kept;
+        removed
+    }
+
+    /// Records the durations of the given results. Ignored tests keep their
+    /// previous entry.
+    pub(crate) fn record(&mut self, results: &[TrialResult]) {
+        for result in results.iter().filter(|r| !r.name.contains(['\n', '\r'])) {
+            if !matches!(result.outcome, Outcome::Ignored | Outcome::NotRun) {
+                self.durations.insert(result.name.clone(), result.duration);
+            }
+        }
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let content = self.durations.iter()
+            .map(|(name, duration)| format!("{} {}\n", duration.as_secs_f64(), name))
+            .collect::<String>();
+        fs::write(path, content)
+    }
+}
@@ -0,0 +1,111 @@
+//! Serializes `Arguments` as TOML for `--dump-config`.
+//!
+//! The keys are the names of the long command line flags (e.g.
+//! `test-threads`), so every entry can be turned back into a flag. Options
+//! that are not set are omitted, as TOML has no null value.
+
+use std::{
+    fmt::{Display, Write},
+    time::Duration,
+};
+
+use crate::{json, Arguments};
+
+
+pub(crate) fn render(args: &Arguments) -> String {
+    let mut out = String::new();
+    let mut flag = |key: &str, value: bool| writeln!(out, "{} = {}", key, value).unwrap();
+    flag("include-ignored", args.include_ignored);
+    flag("ignored", args.ignored);
+    flag("exclude-should-panic", args.exclude_should_panic);
+    flag("test", args.test);
+    flag("bench", args.bench);
+    flag("list", args.list);
+    flag("json-array", args.json_array);
+    flag("names-only", args.names_only);
+    flag("verbose-list", args.verbose_list);
+    flag("dry-run", args.dry_run);
+    flag("nocapture", args.nocapture);
+    flag("strict-stdout", args.strict_stdout);
+    flag("capture", args.capture);
+    flag("keep-temp-on-failure", args.keep_temp_on_failure);
+    flag("exact", args.exact);
+    flag("skip-exact", args.skip_exact);
+    flag("all-tags", args.all_tags);
+    flag("prefix", args.prefix);
+    flag("filter-regex", args.filter_regex);
+    flag("ignore-case", args.ignore_case);
+    flag("quiet", args.quiet);
+    flag("silent", args.silent);
+    flag("shuffle", args.shuffle);
+    flag("ensure-ordered", args.ordered);
+    flag("failed-first", args.failed_first);
+    flag("verify-passes", args.verify_passes);
+    flag("include-host-info", args.include_host_info);
+    flag("strict-width", args.strict_width);
+    flag("no-align", args.no_align);
+    flag("minimal-output", args.minimal_output);
+    flag("summary-by-kind", args.summary_by_kind);
+    flag("allow-empty", args.allow_empty);
+    flag("error-on-ignored", args.error_on_ignored);
+    flag("tee", args.tee);
+    flag("serial-kinds", args.serial_kinds);
+
+    option(&mut out, "test-threads", args.test_threads);
+    option(&mut out, "max-capture-bytes", args.max_capture_bytes);
+    option(&mut out, "max-message-lines", args.max_message_lines);
+    option(&mut out, "max-message-bytes", args.max_message_bytes);
+    option(&mut out, "retries", Some(args.retries));
+    option(&mut out, "failfast-after", args.fail_after);
+    option(&mut out, "bench-samples", Some(args.bench_samples));
+    // Floats are printed with `{:?}` as TOML requires a decimal point.
+    option(&mut out, "allow-failure-rate", args.allow_failure_rate.map(|r| format!("{:?}", r)));
+    let secs = |t: Duration| format!("{:?}", t.as_secs_f64());
+    option(&mut out, "test-timeout", args.timeout.map(secs));
+    option(&mut out, "slow-threshold", args.slow_threshold.map(secs));
+    // TOML integers are signed 64 bit, so larger seeds are written as string.
+    option(&mut out, "shuffle-seed", args.shuffle_seed.map(|seed| match i64::try_from(seed) {
+        Ok(_) => seed.to_string(),
+        Err(_) => string(&seed),
+    }));
+    option(&mut out, "logfile", args.logfile.as_ref().map(string));
+    option(&mut out, "exit-status-file", args.exit_status_file.as_ref().map(string));
+    option(&mut out, "report-socket", args.report_socket.as_ref().map(string));
+    option(&mut out, "aggregate-fd", args.aggregate_fd);
+    option(&mut out, "junit-xml", args.junit_xml.as_ref().map(string));
+    option(&mut out, "history", args.history.as_ref().map(string));
+    option(&mut out, "timings", args.timings.as_ref().map(string));
+    option(&mut out, "time-budget", args.time_budget.map(secs));
+    option(&mut out, "otlp-endpoint", args.otlp_endpoint.as_ref().map(string));
+    option(&mut out, "report-filtered", args.report_filtered.as_ref().map(string));
+    option(&mut out, "baseline", args.baseline.as_ref().map(string));
+    option(&mut out, "partition", args.partition.as_ref().map(string));
+    option(&mut out, "color", args.color.as_ref().map(string));
+    option(&mut out, "format", args.format.as_ref().map(string));
+    option(&mut out, "sort-by", args.sort_by.as_ref().map(string));
+
+    let skip = args.skip.iter().map(string).collect::<Vec<_>>();
+    writeln!(out, "skip = [{}]", skip.join(", ")).unwrap();
+    let kinds = args.kinds.iter().map(string).collect::<Vec<_>>();
+    writeln!(out, "kind = [{}]", kinds.join(", ")).unwrap();
+    let tags = args.tags.iter().map(string).collect::<Vec<_>>();
+    writeln!(out, "tag = [{}]", tags.join(", ")).unwrap();
+    let exclude_tags = args.exclude_tags.iter().map(string).collect::<Vec<_>>();
+    writeln!(out, "exclude-tag = [{}]", exclude_tags.join(", ")).unwrap();
+    let filters = args.filters.iter().map(string).collect::<Vec<_>>();
+    writeln!(out, "filters = [{}]", filters.join(", ")).unwrap();
+
+    out
+}
+
+fn option(out: &mut String, key: &str, value: Option<impl Display>) {
+    if let Some(value) = value {
+        writeln!(out, "{} = {}", key, value).unwrap();
+    }
+}
+
+/// Formats the value as TOML basic string. Its escape sequences are a
+/// superset of the ones used by `json::escape`.
+fn string(value: &impl Display) -> String {
+    format!("\"{}\"", json::escape(&value.to_string()))
+}
@@ -0,0 +1,86 @@
+use std::{thread, time::Duration};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test_with_control("dog", |control| {
+            control.abort();
+            Err("environment is unusable".into())
+        }),
+        Trial::test("fox", || Ok(())),
+        Trial::test("bunny", || Ok(())),
+    ]
+}
+
+#[test]
+fn abort_in_main_thread() {
+    let (c, out) = do_run(args(["--test-threads", "1"]), tests());
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(c.num_ignored, 0);
+    assert_eq!(c.num_not_run, 2);
+    assert_log!(out, "
+        running 4 tests
+        test cat   ... ok
+        test dog   ... FAILED
+        test fox   ... not run
+        test bunny ... not run
+        warning: the run was aborted, 2 tests were not run
+
+        failures:
+
+        ---- dog stdout ----
+        environment is unusable
+
+
+        failures:
+            dog
+
+        to rerun: <exe> --exact dog
+
+        test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            2 not run; finished in 0.00s
+    ");
+}
+
+#[test]
+fn abort_in_thread_pool() {
+    let mut tests = vec![Trial::test_with_control("abort", |control| {
+        control.abort();
+        Ok(())
+    })];
+    tests.extend((0..20).map(|i| Trial::test(format!("t{i}"), || {
+        thread::sleep(Duration::from_millis(20));
+        Ok(())
+    })));
+
+    let (c, out) = do_run(args(["--test-threads", "2"]), tests);
+    assert_eq!(c.num_failed, 0);
+    assert_eq!(c.num_ignored, 0);
+    assert_eq!(c.num_passed + c.num_not_run, 21);
+    assert!(c.num_not_run >= 18, "{:?}", c);
+    assert!(out.contains(&format!("the run was aborted, {} tests were not run", c.num_not_run)));
+}
+
+#[test]
+fn is_aborted() {
+    let tests = vec![
+        Trial::test_with_control("cat", |control| {
+            assert!(!control.is_aborted());
+            control.abort();
+            assert!(control.is_aborted());
+            Ok(())
+        }),
+    ];
+    let (c, out) = do_run(args([]), tests);
+    assert_eq!(c.num_passed, 1);
+    assert!(!out.contains("warning"));
+}
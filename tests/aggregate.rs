@@ -0,0 +1,76 @@
+use std::io::BufReader;
+
+use common::{args, do_run};
+use libtest_mimic::{aggregate_from, Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn binary_a() -> Vec<Trial> {
+    vec![
+        Trial::test("a1", || Ok(())),
+        Trial::test("a2", || Err("nope".into())),
+        Trial::test("a3", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+fn binary_b() -> Vec<Trial> {
+    vec![
+        Trial::test("b1", || Ok(())),
+        Trial::test("b2", || Ok(())),
+        Trial::bench("b3", |_| Ok(None)),
+    ]
+}
+
+#[test]
+fn merges_event_streams() {
+    let (_, a) = do_run(args(["--format=json"]), binary_a());
+    let (_, b) = do_run(args(["--format=json", "--skip=b2"]), binary_b());
+    let events = format!("{}not an event\n{}", a, b);
+
+    let c = aggregate_from(BufReader::new(events.as_bytes())).unwrap();
+    assert_eq!(c, Conclusion {
+        num_filtered_out: 1,
+        num_passed: 3,
+        num_failed: 1,
+        num_ignored: 1,
+        ..Default::default()
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn streams_to_fd() {
+    use std::{fs::{self, File}, os::unix::io::AsRawFd, path::Path};
+
+    let path = Path::new(common::TEMPDIR).join("libtest_mimic_aggregate.txt");
+    let file = File::create(&path).unwrap();
+    let fd = file.as_raw_fd().to_string();
+
+    let (c_a, out) = do_run(args(["--aggregate-fd", &fd]), binary_a());
+    assert!(!out.contains("warning"));
+    let (c_b, _) = do_run(args(["--aggregate-fd", &fd, "--bench"]), binary_b());
+    drop(file);
+
+    let events = fs::read_to_string(&path).unwrap();
+    assert!(events.lines().all(|l| l.starts_with('{') && l.ends_with('}')));
+    let c = aggregate_from(BufReader::new(events.as_bytes())).unwrap();
+    assert_eq!(c, Conclusion {
+        num_filtered_out: c_a.num_filtered_out + c_b.num_filtered_out,
+        num_passed: c_a.num_passed + c_b.num_passed,
+        num_failed: c_a.num_failed + c_b.num_failed,
+        num_ignored: c_a.num_ignored + c_b.num_ignored,
+        num_measured: c_a.num_measured + c_b.num_measured,
+        ..Default::default()
+    });
+    assert_eq!(c.num_ignored, 3);
+}
+
+#[test]
+fn invalid_fd() {
+    let (c, out) = do_run(args(["--aggregate-fd=999"]), binary_a());
+    assert_eq!(c.num_passed, 1);
+    assert!(out.starts_with("warning: "), "{}", out);
+}
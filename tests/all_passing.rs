@@ -20,11 +20,8 @@ fn tests() -> Vec<Trial> {
 fn normal() {
     check(args([]), tests, 3,
         Conclusion {
-            num_filtered_out: 0,
             num_passed: 3,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test foo   ... ok
@@ -40,9 +37,7 @@ fn filter_one() {
         Conclusion {
             num_filtered_out: 2,
             num_passed: 1,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "test foo ... ok",
     );
@@ -54,9 +49,7 @@ fn filter_two() {
         Conclusion {
             num_filtered_out: 1,
             num_passed: 2,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test bar   ... ok
@@ -72,9 +65,7 @@ fn filter_exact() {
         Conclusion {
             num_filtered_out: 2,
             num_passed: 1,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "test bar ... ok",
     );
@@ -86,9 +77,7 @@ fn filter_two_and_skip() {
         Conclusion {
             num_filtered_out: 2,
             num_passed: 1,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "test bar ... ok",
     );
@@ -98,11 +87,8 @@ fn filter_two_and_skip() {
 fn skip_nothing() {
     check(args(["--skip", "peter"]), tests, 3,
         Conclusion {
-            num_filtered_out: 0,
             num_passed: 3,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test foo   ... ok
@@ -118,9 +104,7 @@ fn skip_two() {
         Conclusion {
             num_filtered_out: 2,
             num_passed: 1,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "test foo ... ok"
     );
@@ -132,9 +116,7 @@ fn skip_exact() {
         Conclusion {
             num_filtered_out: 1,
             num_passed: 2,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test foo   ... ok
@@ -147,11 +129,8 @@ fn skip_exact() {
 fn terse_output() {
     let (c, out) = do_run(args(["--format", "terse"]), tests());
     assert_eq!(c, Conclusion {
-        num_filtered_out: 0,
         num_passed: 3,
-        num_failed: 0,
-        num_ignored: 0,
-        num_measured: 0,
+        ..Default::default()
     });
     assert_log!(out, "
         running 3 tests
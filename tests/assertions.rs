@@ -0,0 +1,56 @@
+use common::{args, check};
+use libtest_mimic::{Trial, Conclusion, Failed};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test_with_assertions("counts", || Ok(3)),
+        Trial::test_with_assertions("counts_more", || Ok(10)),
+        Trial::test_with_assertions("fails", || Err(Failed::from("bad").with_assertions(2))),
+        Trial::test("plain", || Ok(())),
+        Trial::test_with_assertions("ignored", || Ok(100)).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn aggregated() {
+    check(args([]), tests, 5,
+        Conclusion {
+            num_passed: 3,
+            num_failed: 1,
+            num_ignored: 1,
+            num_assertions: 15,
+            ..Default::default()
+        },
+        "
+            test counts      ... ok
+            test counts_more ... ok
+            test fails       ... FAILED
+            test plain       ... ok
+            test ignored     ... ignored
+
+            failures:
+
+            ---- fails stdout ----
+            bad
+
+
+            failures:
+                fails
+
+            to rerun: <exe> --exact fails
+        ",
+    );
+}
+
+#[test]
+fn printed_in_summary() {
+    let (_, out) = common::do_run(args([]), tests());
+    assert!(out.contains("0 filtered out; 15 assertions; finished in"));
+
+    let (_, out) = common::do_run(args([]), vec![Trial::test("plain", || Ok(()))]);
+    assert!(!out.contains("assertions"));
+}
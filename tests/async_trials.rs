@@ -0,0 +1,110 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{args, check, disable_backtraces};
+use libtest_mimic::{run_async, Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test_async("cat", || async { Ok(()) }),
+        Trial::test_async("dog", || async { Err("was not a good boy".into()) }),
+        Trial::test_async("fox", || async { panic!("jumped") }),
+        Trial::test("bunny", || Ok(())),
+    ]
+}
+
+#[test]
+fn blocking() {
+    check(args([]), tests, 4,
+        Conclusion {
+            num_passed: 2,
+            num_failed: 2,
+            ..Default::default()
+        },
+        "
+            test cat   ... ok
+            test dog   ... FAILED
+            test fox   ... FAILED
+            test bunny ... ok
+
+            failures:
+
+            ---- dog stdout ----
+            was not a good boy
+
+            ---- fox stdout ----
+            test panicked: jumped
+
+
+            failures:
+                dog
+                fox
+
+            to rerun: <exe> --exact dog fox
+        ",
+    );
+}
+
+#[test]
+fn spawned() {
+    let args = args(["--test-threads", "2"]);
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let conclusion = run_async(&args, tests(), Some(runtime.handle()));
+    assert_eq!(conclusion.num_passed, 2);
+    assert_eq!(conclusion.num_failed, 2);
+}
+
+#[test]
+fn concurrency_is_limited() {
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let tests = (0..6).map(|i| {
+        let running = running.clone();
+        let max_running = max_running.clone();
+        Trial::test_async(format!("t{}", i), move || async move {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            tokio::task::spawn_blocking(|| thread::sleep(Duration::from_millis(100)))
+                .await
+                .unwrap();
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        })
+    }).collect();
+
+    let start = Instant::now();
+    let conclusion = run_async(&args(["--test-threads", "2"]), tests, None);
+    assert_eq!(conclusion.num_passed, 6);
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
+
+#[test]
+fn should_panic() {
+    disable_backtraces();
+    let tests = || vec![
+        Trial::test_async("cat", || async { panic!("meow") }).with_should_panic(Some("meow")),
+        Trial::test_async("dog", || async { Ok(()) }).with_should_panic(None),
+    ];
+    for threads in ["1", "2"] {
+        let args = args(["--test-threads", threads]);
+        let conclusion = run_async(&args, tests(), None);
+        assert_eq!(conclusion.num_passed, 1);
+        assert_eq!(conclusion.num_failed, 1);
+    }
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "backtrace")]
+
+use std::env;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+#[inline(never)]
+fn failing_helper() {
+    panic!("oh no");
+}
+
+#[test]
+fn backtrace_is_printed_with_failures() {
+    env::set_var("RUST_LIB_BACKTRACE", "1");
+    let tests = vec![
+        Trial::test("panics", || {
+            failing_helper();
+            Ok(())
+        }),
+        Trial::test("fails", || Err("no panic".into())),
+    ];
+
+    let (c, out) = do_run(args(["--test-threads=1"]), tests);
+    assert_eq!(c.num_failed, 2);
+
+    let panics = out.split("---- panics stdout ----").nth(1).unwrap();
+    let panics = panics.split("---- fails stdout ----").next().unwrap();
+    assert!(panics.starts_with("\ntest panicked: oh no\nstack backtrace:\n"), "{}", panics);
+    assert!(panics.contains("failing_helper"), "{}", panics);
+    assert!(panics.ends_with("\n\n"), "{:?}", panics);
+
+    let fails = out.split("---- fails stdout ----").nth(1).unwrap();
+    assert!(!fails.contains("stack backtrace:"), "{}", fails);
+}
@@ -0,0 +1,44 @@
+use std::{fs, path::Path};
+
+use common::{args, do_run, TEMPDIR};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Ok(())).with_kind("animal"),
+        Trial::bench("fox", |_| Ok(None)),
+    ]
+}
+
+fn list_against(name: &str, baseline: &str) -> (libtest_mimic::Conclusion, String) {
+    let path = Path::new(TEMPDIR).join(format!("libtest_mimic_baseline_{}.txt", name));
+    fs::write(&path, baseline).unwrap();
+    do_run(args(["--list", "--baseline", path.to_str().unwrap()]), tests())
+}
+
+#[test]
+fn unchanged() {
+    let (_, list) = do_run(args(["--list"]), tests());
+    let (c, out) = list_against("unchanged", &list);
+    assert!(!c.has_failed());
+    assert_log!(out, "
+        0 added, 0 removed compared to the baseline
+    ");
+}
+
+#[test]
+fn added_and_removed() {
+    let (c, out) = list_against("changed", "cat: test\nfox: bench\nowl: test\n\n");
+    assert!(c.has_failed());
+    assert_eq!(c.num_failed, 2);
+    assert_log!(out, "
+        + [animal] dog: test
+        - owl: test
+        1 added, 1 removed compared to the baseline
+    ");
+}
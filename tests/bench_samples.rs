@@ -0,0 +1,59 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::{args, do_run};
+use libtest_mimic::{Failed, Trial};
+
+#[macro_use]
+mod common;
+
+
+fn counting_bench(calls: &Arc<AtomicUsize>) -> Trial {
+    let calls = calls.clone();
+    Trial::bench_fn("count", move || {
+        Ok(move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+        })
+    })
+}
+
+#[test]
+fn default_samples() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (c, _) = do_run(args(["--bench"]), vec![counting_bench(&calls)]);
+    assert_eq!(calls.load(Ordering::SeqCst), 50);
+    assert_eq!(c.num_measured, 1);
+}
+
+#[test]
+fn custom_samples() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (c, _) = do_run(args(["--bench", "--bench-samples", "7"]), vec![counting_bench(&calls)]);
+    assert_eq!(calls.load(Ordering::SeqCst), 7);
+    assert_eq!(c.num_measured, 1);
+
+    // At least one sample is always taken.
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (c, _) = do_run(args(["--bench", "--bench-samples=0"]), vec![counting_bench(&calls)]);
+    assert_eq!(c.num_measured, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_mode_runs_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (c, _) = do_run(args(["--bench-samples=7"]), vec![counting_bench(&calls)]);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_measured, 0);
+}
+
+#[test]
+fn setup_failure() {
+    let bench = Trial::bench_fn("broken", || Err::<fn(), _>(Failed::from("no input")));
+    let (c, out) = do_run(args(["--bench"]), vec![bench]);
+    assert_eq!(c.num_failed, 1);
+    assert!(out.contains("no input"));
+}
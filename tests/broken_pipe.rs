@@ -0,0 +1,56 @@
+use std::{
+    env,
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use libtest_mimic::{run, Arguments, Trial};
+
+
+const CHILD_ENV: &str = "LIBTEST_MIMIC_BROKEN_PIPE_CHILD";
+
+/// Runs in a child process (see `closed_stdout_does_not_panic`), as only
+/// stdout can be a pipe.
+#[test]
+#[ignore]
+fn child() {
+    env::var(CHILD_ENV).expect("only run by `closed_stdout_does_not_panic`");
+    let tests = (0..20)
+        .map(|i| Trial::test(format!("t{i}"), || {
+            thread::sleep(Duration::from_millis(10));
+            Ok(())
+        }))
+        .collect();
+    let args = Arguments::from_iter(["<child>", "--test-threads", "1", "--color", "never"]);
+    let conclusion = run(&args, tests);
+
+    // Exit before the harness running this test prints to the closed pipe.
+    std::process::exit(if conclusion.num_passed == 20 { 0 } else { 1 });
+}
+
+#[test]
+fn closed_stdout_does_not_panic() {
+    let mut child = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "child", "--ignored", "--nocapture"])
+        .env(CHILD_ENV, "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Close the pipe right after the first test line, like `head` would.
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    while !line.starts_with("test t0") {
+        line.clear();
+        stdout.read_line(&mut line).unwrap();
+    }
+    drop(stdout);
+
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
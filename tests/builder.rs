@@ -0,0 +1,39 @@
+use common::{args, check};
+use libtest_mimic::{Trial, Conclusion};
+
+#[macro_use]
+mod common;
+
+
+#[test]
+fn chained_setters() {
+    let trial = Trial::test("old", || Ok(())).with_name("new").with_kind("parse").ignored();
+    assert_eq!(trial.name(), "new");
+    assert_eq!(trial.kind(), "parse");
+    assert!(trial.has_ignored_flag());
+    assert!(trial.is_test());
+
+    let trial = Trial::bench("b", |_| Ok(None)).with_kind("k");
+    assert!(trial.is_bench());
+    assert!(!trial.has_ignored_flag());
+}
+
+#[test]
+fn run_built_trials() {
+    let tests = || vec![
+        Trial::test("a", || Ok(())).with_kind("parse"),
+        Trial::test("b", || Ok(())).with_name("renamed").ignored(),
+    ];
+
+    check(args([]), tests, 2,
+        Conclusion {
+            num_passed: 1,
+            num_ignored: 1,
+            ..Default::default()
+        },
+        "
+            test [parse] a       ... ok
+            test         renamed ... ignored
+        ",
+    );
+}
@@ -0,0 +1,68 @@
+//! This file intentionally contains a single test: redirecting stdout and
+//! stderr affects the whole process, including other tests running in
+//! parallel.
+#![cfg(unix)]
+
+use std::io::Write;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+#[test]
+fn output_of_failed_tests_is_shown() {
+    // `println!` would be captured by the test harness running this file, so
+    // we write to stdout and stderr directly.
+    let tests = || vec![
+        Trial::test("passes", || {
+            writeln!(std::io::stdout(), "passing output").unwrap();
+            Ok(())
+        }),
+        Trial::test("fails", || {
+            writeln!(std::io::stdout(), "to stdout").unwrap();
+            writeln!(std::io::stderr(), "to stderr").unwrap();
+            Err("nope".into())
+        }),
+        Trial::test("panics", || {
+            write!(std::io::stdout(), "no newline").unwrap();
+            panic!("boom");
+        }),
+    ];
+
+    let mut args = args(["--capture"]);
+    args.test_threads = Some(1);
+    let (c, out) = do_run(args.clone(), tests());
+    assert_eq!(c.num_failed, 2);
+    assert!(!out.contains("passing output"));
+    let failures = out.split_once("\nfailures:\n").unwrap().1;
+    let failures = failures.split("\nfailures:\n").next().unwrap();
+    assert_log!(failures, "
+        ---- fails stdout ----
+        to stdout
+        to stderr
+        nope
+
+        ---- panics stdout ----
+        no newline
+        test panicked: boom
+    ");
+
+    // Output is truncated with `--max-capture-bytes`.
+    let mut truncated_args = args.clone();
+    truncated_args.max_capture_bytes = Some(10);
+    let big = || vec![Trial::test("fails", || {
+        std::io::stdout().write_all(&[b'x'; 100_000]).unwrap();
+        writeln!(std::io::stderr(), "short").unwrap();
+        Err("nope".into())
+    })];
+    let (_, out) = do_run(truncated_args, big());
+    assert!(out.contains("---- fails stdout ----\nxxxxxxxxxx... [truncated]\nshort\nnope\n"));
+
+    // Nothing is captured with `--nocapture`.
+    args.nocapture = true;
+    let (_, out) = do_run(args, tests());
+    assert!(out.contains("---- fails stdout ----\nnope\n"));
+}
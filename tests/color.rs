@@ -0,0 +1,82 @@
+use common::{args, do_run};
+use libtest_mimic::{ColorSetting, Trial};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("nope".into())),
+    ]
+}
+
+// This is the only test in this binary that changes the environment.
+#[test]
+fn logfile_is_only_colored_with_always() {
+    std::env::set_var("CLICOLOR_FORCE", "1");
+
+    for color in [None, Some("--color=auto"), Some("--color=never")] {
+        let args = match color {
+            Some(color) => args([color]),
+            None => args([]),
+        };
+        let (_, out) = do_run(args, tests());
+        assert!(!out.contains('\u{1b}'), "{:?}: {:?}", color, out);
+    }
+
+    let (_, out) = do_run(args(["--color=always"]), tests());
+    assert!(out.contains("\u{1b}[0m\u{1b}[32mok\u{1b}[0m"), "{:?}", out);
+}
+
+fn resolve(setting: ColorSetting, env: &[(&str, &str)]) -> ColorSetting {
+    setting.resolve_env(|name| {
+        env.iter().find(|(n, _)| *n == name).map(|(_, value)| value.to_string())
+    })
+}
+
+#[test]
+fn resolve_env() {
+    use ColorSetting::*;
+
+    assert_eq!(resolve(Auto, &[]), Auto);
+    assert_eq!(resolve(Auto, &[("NO_COLOR", "1")]), Never);
+    assert_eq!(resolve(Auto, &[("NO_COLOR", "")]), Auto);
+    assert_eq!(resolve(Auto, &[("CLICOLOR", "0")]), Never);
+    assert_eq!(resolve(Auto, &[("CLICOLOR", "1")]), Auto);
+    assert_eq!(resolve(Auto, &[("CLICOLOR_FORCE", "1")]), Always);
+    assert_eq!(resolve(Auto, &[("CLICOLOR_FORCE", "0")]), Auto);
+    assert_eq!(resolve(Auto, &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]), Always);
+
+    // Explicit settings take precedence.
+    assert_eq!(resolve(Never, &[("CLICOLOR_FORCE", "1")]), Never);
+    assert_eq!(resolve(Always, &[("NO_COLOR", "1")]), Always);
+}
+
+#[test]
+fn kind_is_colored() {
+    let tests = || vec![Trial::test("cat", || Ok(())).with_kind("animal")];
+
+    let (_, out) = do_run(args(["--color=always"]), tests());
+    let tag = "\u{1b}[0m\u{1b}[2m\u{1b}[34m[animal]\u{1b}[0m";
+    assert!(out.contains(&format!("test {} cat ... ", tag)), "{:?}", out);
+
+    let (_, out) = do_run(args(["--color=never"]), tests());
+    assert!(out.contains("test [animal] cat ... ok"), "{:?}", out);
+}
+
+#[test]
+fn use_color() {
+    assert!(args(["--color=always"]).use_color());
+    assert!(!args(["--color=never"]).use_color());
+
+    // Logfiles are only colored with `--color=always`.
+    assert!(args(["--color=always", "--logfile=log.txt"]).use_color());
+    assert!(!args(["--color=auto", "--logfile=log.txt"]).use_color());
+    assert!(!args(["--logfile=log.txt"]).use_color());
+
+    // With `--tee`, this refers to stdout.
+    assert!(args(["--color=always", "--tee", "--logfile=log.txt"]).use_color());
+    assert!(!args(["--color=never", "--tee", "--logfile=log.txt"]).use_color());
+}
@@ -1,10 +1,13 @@
-use std::{path::Path, iter::repeat_with, collections::HashMap};
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use std::{path::Path, iter::repeat_with, collections::HashMap, env, sync::Once};
 use pretty_assertions::assert_eq;
 
-use libtest_mimic::{run, Arguments, Conclusion, Trial};
+use libtest_mimic::{run, run_detailed, Arguments, Conclusion, Trial};
 
 
-const TEMPDIR: &str = env!("CARGO_TARGET_TMPDIR");
+pub const TEMPDIR: &str = env!("CARGO_TARGET_TMPDIR");
 
 pub fn args<const N: usize>(args: [&str; N]) -> Arguments {
     let mut v = vec!["<dummy-executable>"];
@@ -12,7 +15,31 @@ pub fn args<const N: usize>(args: [&str; N]) -> Arguments {
     Arguments::from_iter(v)
 }
 
+/// Runs `tests` one after another and returns the names of the tests that
+/// ran, in order.
+pub fn run_names<const N: usize>(flags: [&str; N], tests: Vec<Trial>) -> Vec<String> {
+    let mut args = args(flags);
+    args.test_threads = Some(1);
+    args.logfile = Some("/dev/null".into());
+    run_detailed(&args, tests).1.into_iter().map(|r| r.name).collect()
+}
+
+/// Keeps the `backtrace` feature from adding backtraces to the failure
+/// messages if `RUST_BACKTRACE` is set, unless the test file sets
+/// `RUST_LIB_BACKTRACE` itself. Has to be called before the first panic, as
+/// the variable is only read once.
+pub fn disable_backtraces() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        if env::var_os("RUST_LIB_BACKTRACE").is_none() {
+            env::set_var("RUST_LIB_BACKTRACE", "0");
+        }
+    });
+}
+
 pub fn do_run(mut args: Arguments, tests: Vec<Trial>) -> (Conclusion, String) {
+    disable_backtraces();
+
     // Create path to temporary file.
     let suffix = repeat_with(fastrand::alphanumeric).take(10).collect::<String>();
     let path = Path::new(&TEMPDIR).join(format!("libtest_mimic_output_{suffix}.txt"));
@@ -24,6 +51,11 @@ pub fn do_run(mut args: Arguments, tests: Vec<Trial>) -> (Conclusion, String) {
         .expect("Can't read temporary logfile");
     std::fs::remove_file(&path)
         .expect("Can't remove temporary logfile");
+
+    // The rerun hint starts with the path of the test executable.
+    let program = env::args().next().unwrap();
+    let output = output.replace(&format!("to rerun: {} ", program), "to rerun: <exe> ");
+
     (c, output)
 }
 
@@ -82,7 +114,7 @@ pub fn assert_reordered_log(actual: &str, num: u64, expected_lines: &[&str], tai
 macro_rules! assert_log {
     ($actual:expr, $expected:expr) => {
         let actual = $actual;
-        let expected = crate::common::clean_expected_log($expected);
+        let expected = $crate::common::clean_expected_log($expected);
 
         assert_eq!(actual.trim(), expected.trim());
     };
@@ -118,7 +150,14 @@ pub fn check(
 }
 
 fn conclusion_to_output(c: &Conclusion) -> String {
-    let Conclusion { num_filtered_out, num_passed, num_failed, num_ignored, num_measured } = *c;
+    let Conclusion {
+        num_filtered_out,
+        num_passed,
+        num_failed,
+        num_ignored,
+        num_measured,
+        ..
+    } = *c;
     format!(
         "test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out;",
         if num_failed > 0 { "FAILED" } else { "ok" },
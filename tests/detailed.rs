@@ -0,0 +1,63 @@
+use common::args;
+use libtest_mimic::{run_detailed, Measurement, Outcome, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("nope".into())).with_kind("k"),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::bench("bench", |_| Ok(Some(Measurement { avg: 10, variance: 1 }))),
+        Trial::test("filtered", || Ok(())),
+    ]
+}
+
+fn summarize(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Passed { .. } => "passed".into(),
+        Outcome::Failed(failed) => format!("failed: {}", failed.message().unwrap()),
+        Outcome::Ignored => "ignored".into(),
+        Outcome::Measured(m) => format!("measured: {}", m.avg),
+        Outcome::NotRun => "not run".into(),
+    }
+}
+
+#[test]
+fn results_in_order() {
+    let mut args = args(["--bench", "--skip", "filtered", "--logfile", "/dev/null"]);
+    args.test_threads = Some(1);
+    let (c, results) = run_detailed(&args, tests());
+    assert_eq!(c.num_filtered_out, 1);
+
+    let results = results.iter()
+        .map(|r| (r.name.as_str(), r.kind.as_str(), r.is_bench, summarize(&r.outcome)))
+        .collect::<Vec<_>>();
+    assert_eq!(results, [
+        ("passes", "", false, "ignored".to_string()),
+        ("fails", "k", false, "ignored".to_string()),
+        ("ignored", "", false, "ignored".to_string()),
+        ("bench", "", true, "measured: 10".to_string()),
+    ]);
+}
+
+#[test]
+fn results_parallel() {
+    let args = args(["--ensure-ordered", "--logfile", "/dev/null"]);
+    let (c, results) = run_detailed(&args, tests());
+    assert_eq!(c.num_failed, 1);
+
+    let results = results.iter()
+        .map(|r| (r.name.as_str(), r.has_ignored_flag, summarize(&r.outcome)))
+        .collect::<Vec<_>>();
+    assert_eq!(results, [
+        ("bench", false, "passed".to_string()),
+        ("filtered", false, "passed".to_string()),
+        ("ignored", true, "ignored".to_string()),
+        ("passes", false, "passed".to_string()),
+        ("fails", false, "failed: nope".to_string()),
+    ]);
+}
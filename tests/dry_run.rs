@@ -0,0 +1,65 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use common::{args, do_run};
+use libtest_mimic::{Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests(ran: &Arc<AtomicBool>) -> Vec<Trial> {
+    let trial = |name: &str| {
+        let ran = ran.clone();
+        Trial::test(name, move || {
+            ran.store(true, Ordering::SeqCst);
+            Ok(())
+        })
+    };
+    vec![
+        trial("cat"),
+        trial("dog").with_ignored_flag(true),
+        trial("fox"),
+        trial("bunny").with_kind("rabbit"),
+    ]
+}
+
+#[test]
+fn dry_run() {
+    let ran = Arc::new(AtomicBool::new(false));
+    let (c, out) = do_run(args(["--dry-run", "--skip", "fox"]), tests(&ran));
+    assert!(!ran.load(Ordering::SeqCst));
+    assert_eq!(c, Conclusion {
+        num_filtered_out: 1,
+        num_ignored: 1,
+        allow_empty: true,
+        ..Default::default()
+    });
+    assert_log!(out, "
+        running 3 tests
+        test          cat   ... (dry-run)
+        test          dog   ... ignored
+        test [rabbit] bunny ... (dry-run)
+
+        test result: ok. 0 passed; 0 failed; 1 ignored; 0 measured; 1 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn dry_run_terse() {
+    let ran = Arc::new(AtomicBool::new(false));
+    let (_, out) = do_run(args(["--dry-run", "-q", "--include-ignored", "o"]), tests(&ran));
+    assert!(!ran.load(Ordering::SeqCst));
+    assert_log!(out, "
+        running 2 tests
+        test dog ... (dry-run)
+        test fox ... (dry-run)
+
+        test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 2 filtered out; \
+            finished in 0.00s
+    ");
+}
@@ -0,0 +1,83 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use common::{args, do_run};
+use libtest_mimic::{Arguments, Trial};
+
+#[macro_use]
+mod common;
+
+
+/// Turns a dumped configuration back into command line arguments.
+fn toml_to_args(dumped: &str) -> Arguments {
+    let table = dumped.parse::<toml::Value>().unwrap();
+    let mut cli = vec!["<dummy-executable>".to_owned()];
+    let mut filters = Vec::new();
+    for (key, value) in table.as_table().unwrap() {
+        match value {
+            toml::Value::Boolean(true) => cli.push(format!("--{key}")),
+            toml::Value::Boolean(false) => {}
+            toml::Value::String(s) => cli.push(format!("--{key}={s}")),
+            toml::Value::Array(values) if key == "filters" => {
+                filters.extend(values.iter().map(|v| v.as_str().unwrap().to_owned()));
+            }
+            toml::Value::Array(values) => {
+                for v in values {
+                    cli.push(format!("--{key}={}", v.as_str().unwrap()));
+                }
+            }
+            other => cli.push(format!("--{key}={other}")),
+        }
+    }
+    cli.extend(filters);
+    Arguments::from_iter(cli)
+}
+
+fn assert_round_trip(args: Arguments) {
+    let dumped = args.to_toml();
+    assert_eq!(format!("{:?}", toml_to_args(&dumped)), format!("{:?}", args), "{}", dumped);
+}
+
+#[test]
+fn round_trip() {
+    assert_round_trip(args([]));
+    assert_round_trip(args([
+        "--include-ignored", "--test", "--exact", "--ensure-ordered", "--keep-temp-on-failure",
+        "--test-threads=3", "--max-capture-bytes=5", "--allow-failure-rate=2.5",
+        "--max-message-lines=4", "--max-message-bytes=100", "--serial-kinds",
+        "--test-timeout=0.2", "--slow-threshold=1.5",
+        "--shuffle-seed=18446744073709551615", "--logfile", "out \"quoted\".log", "--tee",
+        "--skip", "a", "--skip", "b\\c", "--kind", "", "--kind=k", "--partition=hash:2/3",
+        "--tag=t", "--exclude-tag=x", "--all-tags",
+        "--report-filtered=filtered.txt", "--color=never", "--error-on-ignored",
+        "--format=json", "--skip-exact", "--sort-by=kind", "--allow-empty",
+        "needle", "haystack",
+    ]));
+    assert_round_trip(args([
+        "-q", "--bench", "--shuffle-seed=7", "--partition=count:1/1", "--minimal-output",
+        "--list", "--verbose-list",
+    ]));
+    assert_round_trip(args([
+        "--retries=3", "--failfast-after=2", "--timings=t.txt", "--time-budget=2.5",
+        "--bench-samples=7", "--silent",
+    ]));
+}
+
+#[test]
+fn dump_config_flag() {
+    let ran = Arc::new(AtomicBool::new(false));
+    let ran2 = ran.clone();
+    let tests = vec![Trial::test("a", move || {
+        ran2.store(true, Ordering::SeqCst);
+        Ok(())
+    })];
+
+    let (c, out) = do_run(args(["--dump-config", "--test-threads=2", "--skip", "x"]), tests);
+    assert!(!ran.load(Ordering::SeqCst));
+    assert_eq!(c.num_passed, 0);
+    assert!(out.contains("\ntest-threads = 2\n"), "{}", out);
+    assert!(out.contains("\nskip = [\"x\"]\n"), "{}", out);
+    assert!(!out.contains("dump-config"), "{}", out);
+}
@@ -0,0 +1,37 @@
+use libtest_mimic::Arguments;
+use pretty_assertions::assert_eq;
+
+
+fn parse<const N: usize>(env: Option<&str>, args: [&str; N]) -> Arguments {
+    let mut v = vec!["<dummy-executable>"];
+    v.extend(args);
+    Arguments::from_args_and_env(v, env)
+}
+
+#[test]
+fn without_env() {
+    let args = parse(None, ["--exact", "foo"]);
+    assert_eq!(format!("{:?}", args), format!("{:?}", parse(Some(""), ["--exact", "foo"])));
+    assert!(args.exact);
+    assert_eq!(args.filters, ["foo"]);
+}
+
+#[test]
+fn env_args_come_first() {
+    let args = parse(
+        Some(" --skip 'a b'  --test-threads=2 --exact --logfile=env.log  env "),
+        ["--test-threads=3", "--skip", "c", "cli"],
+    );
+    assert!(args.exact);
+    assert_eq!(args.test_threads, Some(3));
+    assert_eq!(args.logfile.as_deref(), Some("env.log"));
+    assert_eq!(args.skip, ["a b", "c"]);
+    assert_eq!(args.filters, ["env", "cli"]);
+}
+
+#[test]
+fn shell_quoting() {
+    let args = parse(Some(r#"--logfile "out \"x\" \y.log" needle\ one 'it'"s" ''"#), []);
+    assert_eq!(args.logfile.as_deref(), Some(r#"out "x" \y.log"#));
+    assert_eq!(args.filters, ["needle one", "its", ""]);
+}
@@ -0,0 +1,84 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Err("was not a good boy".into())),
+        Trial::test("fox", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn exit_code() {
+    let code = |a: &[&str]| {
+        let mut v = vec!["<dummy-executable>"];
+        v.extend(a);
+        let (c, _) = do_run(libtest_mimic::Arguments::from_iter(v), tests());
+        c.exit_code()
+    };
+
+    assert_eq!(code(&[]), 101);
+    assert_eq!(code(&["cat"]), 0);
+    assert_eq!(code(&["fox"]), 0);
+    assert_eq!(code(&["bunny"]), 2);
+    assert_eq!(code(&["--allow-empty", "bunny"]), 0);
+    assert_eq!(code(&["--allow-empty", "cat"]), 0);
+    assert_eq!(code(&["--list", "bunny"]), 0);
+    assert_eq!(code(&["--dry-run", "cat"]), 0);
+}
+
+#[test]
+fn empty_warning() {
+    let (c, out) = do_run(args(["bunny"]), tests());
+    assert!(!c.allow_empty);
+    assert_log!(out, "
+        running 0 tests
+        warning: no tests to run
+
+        test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 3 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn allow_empty() {
+    let (c, out) = do_run(args(["--allow-empty", "bunny"]), tests());
+    assert!(c.allow_empty);
+    assert_eq!(c.exit_code(), 0);
+    assert!(!out.contains("warning"), "{}", out);
+}
+
+#[test]
+fn error_on_ignored() {
+    let code = |a: &[&str]| {
+        let mut v = vec!["<dummy-executable>", "--error-on-ignored"];
+        v.extend(a);
+        let (c, _) = do_run(libtest_mimic::Arguments::from_iter(v), tests());
+        (c.has_failed(), c.exit_code())
+    };
+
+    assert_eq!(code(&["cat"]), (false, 0));
+    assert_eq!(code(&["fox"]), (true, 101));
+    assert_eq!(code(&["--include-ignored", "fox"]), (false, 0));
+    assert_eq!(code(&["--allow-failure-rate=1", "--skip=cat"]), (true, 101));
+}
+
+#[test]
+fn error_on_ignored_output() {
+    let (c, out) = do_run(args(["--error-on-ignored", "--test-threads=1", "--skip=dog"]), tests());
+    assert!(c.ignored_is_error);
+    assert_log!(out, "
+        running 2 tests
+        test cat ... ok
+        test fox ... ignored
+
+        test result: FAILED. 1 passed; 0 failed; 1 ignored; 0 measured; 1 filtered out; \
+            finished in 0.00s
+    ");
+}
@@ -0,0 +1,98 @@
+use std::{fs, path::Path};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+const TEMPDIR: &str = env!("CARGO_TARGET_TMPDIR");
+
+#[test]
+fn written_after_run() {
+    let path = Path::new(TEMPDIR).join("libtest_mimic_exit_status_complete.json");
+    let args = args(["--exit-status-file", path.to_str().unwrap(), "--skip", "skipped"]);
+    let (c, _) = do_run(args, vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("nope".into())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("skipped", || Ok(())),
+    ]);
+    assert!(c.has_failed());
+
+    let status = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        status.trim(),
+        r#"{"complete":true,"passed":1,"failed":1,"ignored":1,"measured":0,"filtered_out":1}"#,
+    );
+}
+
+#[cfg(all(unix, feature = "sigterm"))]
+mod sigterm {
+    use std::{env, fs, path::Path, process::Command, thread, time::Duration};
+    use libtest_mimic::{run, Arguments, Trial};
+    use super::TEMPDIR;
+
+    const CHILD_ENV: &str = "LIBTEST_MIMIC_SIGTERM_STATUS_FILE";
+
+    /// Runs in a child process (see `partial_status_on_sigterm`): after a
+    /// first, finished run, the third test of the second run sends SIGTERM to
+    /// its own process, like a supervisor would.
+    #[test]
+    #[ignore]
+    fn sigterm_child() {
+        let path = env::var(CHILD_ENV).expect("only run by `partial_status_on_sigterm`");
+
+        // The handler of this run must not interfere with the second one.
+        let first = Arguments {
+            exit_status_file: Some(format!("{}.first", path)),
+            ..Arguments::default()
+        };
+        let _ = run(&first, vec![Trial::test("first", || Ok(()))]);
+
+        let args = Arguments {
+            exit_status_file: Some(path),
+            test_threads: Some(1),
+            ..Arguments::default()
+        };
+
+        let _ = run(&args, vec![
+            Trial::test("passes", || Ok(())),
+            Trial::test("fails", || Err("nope".into())),
+            Trial::test("killed", || {
+                let pid = std::process::id().to_string();
+                Command::new("kill").args(["-TERM", &pid]).status().unwrap();
+                thread::sleep(Duration::from_secs(10));
+                Ok(())
+            }),
+            Trial::test("never_runs", || Ok(())),
+        ]);
+        unreachable!("process should have been terminated");
+    }
+
+    #[test]
+    fn partial_status_on_sigterm() {
+        let path = Path::new(TEMPDIR).join("libtest_mimic_exit_status_partial.json");
+        let status = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "sigterm::sigterm_child", "--ignored"])
+            .env(CHILD_ENV, &path)
+            .output()
+            .unwrap()
+            .status;
+        assert!(!status.success());
+
+        let first = path.with_extension("json.first");
+        let written = fs::read_to_string(&first).unwrap();
+        fs::remove_file(&first).unwrap();
+        assert!(written.starts_with(r#"{"complete":true,"passed":1,"#), "{}", written);
+
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            written.trim(),
+            r#"{"complete":false,"passed":1,"failed":1,"ignored":0,"measured":0,"filtered_out":0}"#,
+        );
+    }
+}
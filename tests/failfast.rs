@@ -0,0 +1,95 @@
+use common::{args, do_run};
+use libtest_mimic::{Failed, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    let fail = |name: &str| Trial::test(name, || Err(Failed::from("broken")));
+    vec![
+        Trial::test("cat", || Ok(())),
+        fail("dog"),
+        fail("fox"),
+        Trial::test("owl", || Ok(())),
+        fail("bunny"),
+        Trial::test("frog", || Ok(())),
+    ]
+}
+
+/// Returns the number of passed, failed and not run tests.
+fn counts<const N: usize>(flags: [&str; N]) -> (u64, u64, u64) {
+    let (c, _) = do_run(args(flags), tests());
+    (c.num_passed, c.num_failed, c.num_not_run)
+}
+
+#[test]
+fn stops_after_n_failures() {
+    assert_eq!(counts(["--test-threads=1"]), (3, 3, 0));
+    assert_eq!(counts(["--test-threads=1", "--failfast-after=1"]), (1, 1, 4));
+    assert_eq!(counts(["--test-threads=1", "--failfast-after=2"]), (1, 2, 3));
+    assert_eq!(counts(["--test-threads=1", "--failfast-after=3"]), (2, 3, 1));
+    assert_eq!(counts(["--test-threads=1", "--failfast-after=4"]), (3, 3, 0));
+}
+
+#[test]
+fn not_run_is_not_ignored() {
+    let mut trials = tests();
+    trials.push(Trial::test("ignored", || Ok(())).with_ignored_flag(true));
+    let (c, _) = do_run(args(["--test-threads=1", "--failfast-after=1"]), trials);
+    // The ignored test stays ignored, only the remaining ones are not run.
+    assert_eq!((c.num_passed, c.num_failed, c.num_ignored, c.num_not_run), (1, 1, 1, 4));
+
+    // Tests that were not run do not trip `--error-on-ignored`.
+    let flags = [
+        "--test-threads=1",
+        "--failfast-after=1",
+        "--allow-failure-rate=50",
+        "--error-on-ignored",
+    ];
+    let (c, _) = do_run(args(flags), tests());
+    assert_eq!((c.num_ignored, c.num_not_run), (0, 4));
+    assert_eq!(c.exit_code(), 0);
+}
+
+#[test]
+fn output() {
+    let (_, out) = do_run(args(["--test-threads=1", "--failfast-after=2"]), tests());
+    assert_log!(out, "
+        running 6 tests
+        test cat   ... ok
+        test dog   ... FAILED
+        test fox   ... FAILED
+        test owl   ... not run
+        test bunny ... not run
+        test frog  ... not run
+        warning: the run was aborted, 3 tests were not run
+
+        failures:
+
+        ---- dog stdout ----
+        broken
+
+        ---- fox stdout ----
+        broken
+
+
+        failures:
+            dog
+            fox
+
+        to rerun: <exe> --exact dog fox
+
+        test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; \
+            3 not run; finished in 0.00s
+    ");
+}
+
+#[test]
+fn parallel() {
+    // Which tests are started before the run is aborted depends on timing.
+    let (c, _) = do_run(args(["--test-threads=2", "--failfast-after=1"]), tests());
+    assert!(c.num_failed >= 1);
+    assert_eq!(c.num_passed + c.num_failed + c.num_not_run, 6);
+}
@@ -0,0 +1,68 @@
+use std::{env, process::Command};
+
+use common::{args, do_run};
+use libtest_mimic::{run, Arguments, Trial};
+
+#[macro_use]
+mod common;
+
+
+const CHILD_ENV: &str = "LIBTEST_MIMIC_FAILURE_RATE_ARGS";
+
+/// 9 passing and 1 failing test, i.e. a failure rate of 10%.
+fn tests() -> Vec<Trial> {
+    (0..10)
+        .map(|i| Trial::test(format!("t{i}"), move || {
+            if i == 3 {
+                Err("nope".into())
+            } else {
+                Ok(())
+            }
+        }))
+        .collect()
+}
+
+#[test]
+fn parse() {
+    assert_eq!(args(["--allow-failure-rate=2.5"]).allow_failure_rate, Some(2.5));
+    assert_eq!(args([]).allow_failure_rate, None);
+}
+
+#[test]
+fn failures_still_reported() {
+    let (c, out) = do_run(args(["--allow-failure-rate=50"]), tests());
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(c.num_allowed_failures, 5);
+    assert!(out.contains("---- t3 stdout ----\nnope"));
+    assert!(out.contains("test result: FAILED. 9 passed; 1 failed;"));
+}
+
+/// Runs in a child process (see `exit_code_at_boundary`) and exits with the
+/// exit code chosen by `Conclusion::exit`.
+#[test]
+#[ignore]
+fn child() {
+    let extra = env::var(CHILD_ENV).expect("only run by `exit_code_at_boundary`");
+    let mut args = vec!["<child>", "--logfile", "/dev/null"];
+    args.extend(extra.split_whitespace());
+    run(&Arguments::from_iter(args), tests()).exit();
+}
+
+#[test]
+fn exit_code_at_boundary() {
+    let exit_code = |extra: &str| {
+        Command::new(env::current_exe().unwrap())
+            .args(["--exact", "child", "--ignored", "--nocapture"])
+            .env(CHILD_ENV, extra)
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+
+    assert_eq!(exit_code(""), Some(101));
+    assert_eq!(exit_code("--allow-failure-rate=0"), Some(101));
+    assert_eq!(exit_code("--allow-failure-rate=9.99"), Some(101));
+    assert_eq!(exit_code("--allow-failure-rate=10"), Some(0));
+    assert_eq!(exit_code("--allow-failure-rate=25"), Some(0));
+}
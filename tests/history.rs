@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use common::{args, do_run, TEMPDIR};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+fn tests(order: &Arc<Mutex<Vec<&'static str>>>, failing: &'static str) -> Vec<Trial> {
+    ["a", "b", "c", "d"].into_iter()
+        .map(|name| {
+            let order = order.clone();
+            Trial::test(name, move || {
+                order.lock().unwrap().push(name);
+                if name == failing {
+                    Err("nope".into())
+                } else {
+                    Ok(())
+                }
+            })
+        })
+        .chain([Trial::test("ignored", || Ok(())).with_ignored_flag(true)])
+        .collect()
+}
+
+#[test]
+fn failed_first() {
+    let path = Path::new(TEMPDIR).join("libtest_mimic_history.txt");
+    let _ = fs::remove_file(&path);
+    let mut args = args(["--history", path.to_str().unwrap(), "--failed-first"]);
+    args.test_threads = Some(1);
+
+    // Without history, the normal order is used.
+    let order = Arc::default();
+    let _ = do_run(args.clone(), tests(&order, "c"));
+    assert_eq!(*order.lock().unwrap(), ["a", "b", "c", "d"]);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "passed a\npassed b\nfailed c\npassed d\n",
+    );
+
+    // The previously failed test runs first.
+    let order = Arc::default();
+    let _ = do_run(args.clone(), tests(&order, "d"));
+    assert_eq!(*order.lock().unwrap(), ["c", "a", "b", "d"]);
+
+    // `c` passed this time, `d` failed.
+    let order = Arc::default();
+    let _ = do_run(args.clone(), tests(&order, "none"));
+    assert_eq!(*order.lock().unwrap(), ["d", "a", "b", "c"]);
+
+    // Without `--failed-first`, the history is only recorded.
+    args.failed_first = false;
+    fs::write(&path, "failed d\n").unwrap();
+    let order = Arc::default();
+    let _ = do_run(args, tests(&order, "none"));
+    assert_eq!(*order.lock().unwrap(), ["a", "b", "c", "d"]);
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,46 @@
+use common::run_names;
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("slow_network", || Ok(())),
+        Trial::test("SLOW_disk", || Ok(())),
+        Trial::test("Fast_Network", || Ok(())),
+    ]
+}
+
+#[test]
+fn case_sensitive_by_default() {
+    assert_eq!(run_names(["--skip", "SLOW"], tests()), ["slow_network", "Fast_Network"]);
+    assert_eq!(run_names(["network"], tests()), ["slow_network"]);
+}
+
+#[test]
+fn substring() {
+    assert_eq!(run_names(["--ignore-case", "--skip", "SLOW"], tests()), ["Fast_Network"]);
+    assert_eq!(run_names(["--ignore-case", "network"], tests()), ["slow_network", "Fast_Network"]);
+}
+
+#[test]
+fn exact() {
+    assert_eq!(run_names(["--exact", "fast_network"], tests()), Vec::<String>::new());
+    assert_eq!(run_names(["--ignore-case", "--exact", "fast_network"], tests()), ["Fast_Network"]);
+    assert_eq!(
+        run_names(["--ignore-case", "--exact", "--skip", "slow_disk"], tests()),
+        ["slow_network", "Fast_Network"],
+    );
+}
+
+#[test]
+fn regex() {
+    assert_eq!(run_names(["--filter-regex", "^s"], tests()), ["slow_network"]);
+    assert_eq!(
+        run_names(["--ignore-case", "--filter-regex", "^s"], tests()),
+        ["slow_network", "SLOW_disk"],
+    );
+}
@@ -0,0 +1,75 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Ok(())).with_ignored_flag(true),
+        Trial::test("fox", || Ok(())),
+    ]
+}
+
+/// Returns the number of passed, ignored and filtered out tests.
+fn counts<const N: usize>(flags: [&str; N]) -> (u64, u64, u64) {
+    let (c, _) = do_run(args(flags), tests());
+    (c.num_passed, c.num_ignored, c.num_filtered_out)
+}
+
+#[test]
+fn all_combinations() {
+    // Ignored tests are reported, but not run.
+    assert_eq!(counts([]), (2, 1, 0));
+
+    // Only ignored tests are run.
+    assert_eq!(counts(["--ignored"]), (1, 0, 2));
+
+    // Ignored tests are run along with the others.
+    assert_eq!(counts(["--include-ignored"]), (3, 0, 0));
+}
+
+fn tests_with_reason() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Ok(())).with_ignore_reason("needs a leash"),
+    ]
+}
+
+#[test]
+fn ignore_reason() {
+    let trial = Trial::test("dog", || Ok(())).with_ignore_reason("needs a leash");
+    assert!(trial.has_ignored_flag());
+    assert_eq!(trial.ignore_reason(), Some("needs a leash"));
+
+    let (c, out) = do_run(args(["--test-threads=1"]), tests_with_reason());
+    assert_eq!(c.num_ignored, 1);
+    assert_log!(out, "
+        running 2 tests
+        test cat ... ok
+        test dog ... ignored, needs a leash
+
+        test result: ok. 1 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn ignore_reason_in_json_and_tap() {
+    let (_, out) = do_run(args(["--format=json", "dog"]), tests_with_reason());
+    let event = r#"{ "type": "test", "name": "dog", "event": "ignored", "#.to_owned()
+        + r#""message": "needs a leash" }"#;
+    assert!(out.contains(&event), "{}", out);
+
+    let (_, out) = do_run(args(["--format=tap", "dog"]), tests_with_reason());
+    assert!(out.contains("ok 1 - dog # SKIP needs a leash\n"), "{}", out);
+}
+
+#[test]
+fn ignore_reason_in_verbose_list() {
+    let (_, out) = do_run(args(["--list", "--verbose-list"]), tests_with_reason());
+    assert_eq!(out, "cat  test\ndog  test   (ignored: needs a leash)\n\n2 tests, 0 benchmarks\n");
+}
@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::args;
+use libtest_mimic::{run_iter, Trial};
+
+#[macro_use]
+mod common;
+
+
+/// Number of trials that currently exist.
+static ALIVE: AtomicUsize = AtomicUsize::new(0);
+
+struct Guard;
+
+impl Guard {
+    fn new() -> Self {
+        ALIVE.fetch_add(1, Ordering::SeqCst);
+        Guard
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        ALIVE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn filtered_trials_are_dropped_right_away() {
+    let tests = (0..1000).map(|i| {
+        let guard = Guard::new();
+        Trial::test(format!("fixture_{:03}", i), move || {
+            let _guard = guard;
+            // Only the selected trials (at most 10) are alive at this point.
+            let alive = ALIVE.load(Ordering::SeqCst);
+            if alive <= 10 {
+                Ok(())
+            } else {
+                Err(format!("{} trials alive", alive).into())
+            }
+        })
+    });
+
+    let args = args(["--test-threads=1", "--logfile=/dev/null", "fixture_99"]);
+    let c = run_iter(&args, tests);
+    assert_eq!(c.num_passed, 10);
+    assert_eq!(c.num_failed, 0);
+    assert_eq!(c.num_filtered_out, 990);
+    assert_eq!(ALIVE.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,124 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::args;
+use libtest_mimic::{run_with_writer, Arguments, Trial};
+
+#[macro_use]
+mod common;
+
+
+/// Records when each line was written, to check that events are streamed.
+#[derive(Clone, Default)]
+struct Recorder {
+    buf: Arc<Mutex<Vec<u8>>>,
+    lines: Arc<Mutex<Vec<(Instant, String)>>>,
+}
+
+impl Write for Recorder {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let mut buf = self.buf.lock().unwrap();
+        buf.extend_from_slice(data);
+        while let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+            let line = buf.drain(..=pos).collect::<Vec<_>>();
+            let line = String::from_utf8(line).unwrap().trim_end().to_owned();
+            self.lines.lock().unwrap().push((Instant::now(), line));
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("fast", || Ok(())),
+        Trial::test("slow", || {
+            thread::sleep(Duration::from_millis(300));
+            Ok(())
+        }),
+        Trial::test("fails", || Err("a \"quoted\" message".into())),
+        Trial::test("skipped", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+fn run(args: Arguments) -> (Instant, Vec<(Instant, String)>) {
+    let rec = Recorder::default();
+    let _ = run_with_writer(&args, tests(), rec.clone());
+    let end = Instant::now();
+    let lines = rec.lines.lock().unwrap().clone();
+    (end, lines)
+}
+
+fn index_of(lines: &[(Instant, String)], needle: &str) -> usize {
+    lines.iter()
+        .position(|(_, l)| l == needle)
+        .unwrap_or_else(|| panic!("line {} not found in {:#?}", needle, lines))
+}
+
+#[test]
+fn single_threaded() {
+    let (_, lines) = run(args(["--format=json", "--test-threads=1"]));
+    let mut lines = lines.into_iter().map(|(_, l)| l).collect::<Vec<_>>();
+
+    // The execution time differs from run to run.
+    let summary = lines.pop().unwrap();
+    assert!(summary.starts_with(concat!(
+        r#"{ "type": "suite", "event": "failed", "passed": 2, "failed": 1, "ignored": 1, "#,
+        r#""measured": 0, "filtered_out": 0, "exec_time": "#,
+    )));
+
+    assert_log!(lines.join("\n"), r#"
+        { "type": "suite", "event": "started", "test_count": 4 }
+        { "type": "test", "event": "started", "name": "fast" }
+        { "type": "test", "name": "fast", "event": "ok" }
+        { "type": "test", "event": "started", "name": "slow" }
+        { "type": "test", "name": "slow", "event": "ok" }
+        { "type": "test", "event": "started", "name": "fails" }
+        { "type": "test", "name": "fails", "event": "failed", "stdout": "a \"quoted\" message" }
+        { "type": "test", "event": "started", "name": "skipped" }
+        { "type": "test", "name": "skipped", "event": "ignored" }
+    "#);
+}
+
+#[test]
+fn parallel_events_are_streamed() {
+    let (end, lines) = run(args(["--format=json", "--test-threads=2"]));
+
+    // Every line is a JSON object, nothing else is mixed into the output.
+    assert!(lines.iter().all(|(_, l)| l.starts_with('{') && l.ends_with('}')));
+    assert!(lines.last().unwrap().1.starts_with(r#"{ "type": "suite", "event": "failed""#));
+
+    for name in ["fast", "slow", "fails", "skipped"] {
+        let started = format!(r#"{{ "type": "test", "event": "started", "name": "{}" }}"#, name);
+        let finished = format!(r#"{{ "type": "test", "name": "{}", "event": "#, name);
+        let started = index_of(&lines, &started);
+        let finished = lines.iter().position(|(_, l)| l.starts_with(&finished)).unwrap();
+        assert!(started < finished, "{:#?}", lines);
+        assert_eq!(lines.iter().filter(|(_, l)| l.contains(name)).count(), 2);
+    }
+
+    // The result of the fast test is written right away, not only at the end
+    // of the run.
+    let fast = index_of(&lines, r#"{ "type": "test", "name": "fast", "event": "ok" }"#);
+    assert!(end - lines[fast].0 >= Duration::from_millis(200), "{:#?}", lines);
+}
+
+#[test]
+fn host_info() {
+    let (_, lines) = run(args(["--format=json", "--include-host-info", "--skip=slow"]));
+    let suite: serde_json::Value = serde_json::from_str(&lines[0].1).unwrap();
+    assert_eq!(suite["event"], "started");
+    assert_eq!(suite["test_count"], 3);
+    assert_eq!(suite["host"]["os"], std::env::consts::OS);
+    assert_eq!(suite["host"]["arch"], std::env::consts::ARCH);
+    assert!(suite["host"]["cpus"].as_u64().unwrap() >= 1);
+    assert!(suite["host"]["hostname"].is_string());
+    assert_eq!(suite["host"]["libtest_mimic_version"], env!("CARGO_PKG_VERSION"));
+}
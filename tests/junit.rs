@@ -0,0 +1,71 @@
+use std::{fs, path::Path};
+
+use common::{args, do_run, TEMPDIR};
+use libtest_mimic::{Trial, Measurement};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+#[test]
+fn report() {
+    let path = Path::new(TEMPDIR).join("libtest_mimic_junit.xml");
+    let mut args = args(["--junit-xml", path.to_str().unwrap(), "--skip", "skipped"]);
+    args.test_threads = Some(1);
+    let (c, _) = do_run(args, vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("<1> & \"2\"\nsecond line".into()))
+            .with_kind("parse"),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("skipped", || Ok(())),
+        Trial::bench("bench", |_| Ok(Some(Measurement { avg: 1, variance: 0 }))),
+    ]);
+    assert_eq!(c.num_failed, 1);
+
+    let xml = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let time = regex::Regex::new(r#"time="\d+\.\d{3}""#).unwrap();
+    let xml = time.replace_all(&xml, r#"time="T""#);
+    let suite = junit_suite_name();
+    assert_eq!(xml, format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="{suite}" tests="4" failures="1" errors="0" skipped="1" time="T">
+    <testcase classname="{suite}" name="passes" time="T"/>
+    <testcase classname="parse" name="fails" time="T">
+      <failure message="&lt;1&gt; &amp; &quot;2&quot;">&lt;1&gt; &amp; &quot;2&quot;
+second line</failure>
+    </testcase>
+    <testcase classname="{suite}" name="ignored" time="T">
+      <skipped/>
+    </testcase>
+    <testcase classname="{suite}" name="bench" time="T"/>
+  </testsuite>
+</testsuites>
+"#));
+}
+
+fn junit_suite_name() -> String {
+    std::env::current_exe().unwrap().file_stem().unwrap().to_str().unwrap().to_owned()
+}
+
+#[test]
+fn format_junit_matches_file() {
+    let path = Path::new(TEMPDIR).join("libtest_mimic_format_junit.xml");
+    let args = args([
+        "--format=junit", "--junit-xml", path.to_str().unwrap(), "--test-threads=1",
+        "--shuffle-seed=3",
+    ]);
+    let (c, out) = do_run(args, vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("<oh> no".into())).with_kind("parse"),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]);
+    assert_eq!(c.num_failed, 1);
+
+    let xml = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(out.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#), "{}", out);
+    assert!(out.contains(r#"<failure message="&lt;oh&gt; no">"#), "{}", out);
+    assert_eq!(out, xml);
+}
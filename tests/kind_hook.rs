@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+
+use common::args;
+use libtest_mimic::{run_with_kind_hook, Arguments, Trial};
+
+#[macro_use]
+mod common;
+
+
+type Events = Arc<Mutex<Vec<String>>>;
+
+fn run(args: Arguments) -> Vec<String> {
+    let events = Events::default();
+    let tests = [("a1", "db"), ("b1", "fs"), ("a2", "db"), ("b2", "fs"), ("b3", "fs")]
+        .into_iter()
+        .map(|(name, kind)| {
+            let events = events.clone();
+            Trial::test(name, move || {
+                events.lock().unwrap().push(name.to_owned());
+                Ok(())
+            }).with_kind(kind)
+        })
+        .collect();
+
+    let hook_events = events.clone();
+    let _ = run_with_kind_hook(&args, tests, move |kind| {
+        hook_events.lock().unwrap().push(format!("done {}", kind));
+    });
+
+    let events = events.lock().unwrap().clone();
+    events
+}
+
+#[test]
+fn called_after_last_test_of_kind() {
+    let events = run(args(["--test-threads=1", "--logfile", "/dev/null"]));
+    assert_eq!(events, ["a1", "b1", "a2", "done db", "b2", "b3", "done fs"]);
+}
+
+#[test]
+fn called_once_per_kind_in_parallel() {
+    let events = run(args(["--test-threads=2", "--logfile", "/dev/null"]));
+    assert_eq!(events.len(), 7);
+    for (kind, prefix) in [("db", "a"), ("fs", "b")] {
+        let done = format!("done {}", kind);
+        let positions = events.iter().enumerate()
+            .filter(|(_, e)| **e == done)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(positions.len(), 1);
+        assert!(events[..positions[0]].iter().filter(|e| e.starts_with(prefix)).count() >= 2);
+        assert!(!events[positions[0]..].iter().any(|e| e.starts_with(prefix)));
+    }
+}
+
+#[test]
+fn only_kinds_that_are_run() {
+    let events = run(args(["--test-threads=1", "--logfile", "/dev/null", "--kind=fs", "b1"]));
+    assert_eq!(events, ["b1", "done fs"]);
+}
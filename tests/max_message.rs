@@ -0,0 +1,71 @@
+use common::{args, do_run};
+use libtest_mimic::{run_with_writer, Trial};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("long", || Err("line 1\nline 2\nline 3\nline 4\n".into())),
+        Trial::test("short", || Err("just one".into())),
+        Trial::test("wide", || Err("äöü äöü äöü".into())),
+    ]
+}
+
+/// Returns the message printed for `name` in the list of failures.
+fn message<'a>(out: &'a str, name: &str) -> &'a str {
+    let start = out.find(&format!("---- {} stdout ----\n", name)).unwrap();
+    let rest = &out[start..];
+    let rest = &rest[rest.find('\n').unwrap() + 1..];
+    &rest[..rest.find("\n\n").unwrap()]
+}
+
+/// Runs the tests with output to a writer, which is truncated like the
+/// terminal (unlike the logfile `do_run` uses).
+fn terminal_output<const N: usize>(flags: [&str; N]) -> String {
+    let mut out = Vec::new();
+    let _ = run_with_writer(&args(flags), tests(), &mut out);
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn not_truncated_by_default() {
+    let (_, out) = do_run(args(["--test-threads=1"]), tests());
+    assert_eq!(message(&out, "long"), "line 1\nline 2\nline 3\nline 4");
+    assert_eq!(message(&out, "wide"), "äöü äöü äöü");
+}
+
+#[test]
+fn max_lines() {
+    let out = terminal_output(["--test-threads=1", "--max-message-lines=2"]);
+    assert_eq!(message(&out, "long"), "line 1\nline 2\n... (truncated, 2 more lines)");
+    assert_eq!(message(&out, "short"), "just one");
+
+    let out = terminal_output(["--test-threads=1", "--max-message-lines=0"]);
+    assert_eq!(message(&out, "short"), "... (truncated, 1 more lines)");
+}
+
+#[test]
+fn max_bytes() {
+    let out = terminal_output(["--test-threads=1", "--max-message-bytes=10"]);
+    assert_eq!(message(&out, "long"), "line 1\nlin\n... (truncated, 18 more bytes)");
+    assert_eq!(message(&out, "short"), "just one");
+    // Not cut in the middle of a character.
+    assert_eq!(message(&out, "wide"), "äöü ä\n... (truncated, 11 more bytes)");
+}
+
+#[test]
+fn json_is_not_truncated() {
+    let (_, out) = do_run(
+        args(["--test-threads=1", "--max-message-lines=1", "--format=json", "long"]),
+        tests(),
+    );
+    assert!(out.contains(r#""stdout": "line 1\nline 2\nline 3\nline 4\n""#), "{}", out);
+}
+
+#[test]
+fn logfile_is_not_truncated() {
+    let (_, out) = do_run(args(["--test-threads=1", "--max-message-lines=1"]), tests());
+    assert_eq!(message(&out, "long"), "line 1\nline 2\nline 3\nline 4");
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use common::{args, do_run};
+use libtest_mimic::{Conclusion, RunInfo, Trial};
+use pretty_assertions::assert_eq;
+
+mod common;
+
+
+#[test]
+fn merge_runs() {
+    let (parsers, _) = do_run(args(["--skip", "slow"]), vec![
+        Trial::test("json", || Ok(())),
+        Trial::test("toml", || Ok(())),
+        Trial::test("slow", || Ok(())),
+    ]);
+    let (printers, _) = do_run(args([]), vec![
+        Trial::test("json", || Err("nope".into())),
+        Trial::test("yaml", || Ok(())).with_ignored_flag(true),
+    ]);
+
+    let merged = parsers.merge(&printers);
+    assert_eq!(merged, Conclusion {
+        num_filtered_out: 1,
+        num_passed: 2,
+        num_failed: 1,
+        num_ignored: 1,
+        ..Default::default()
+    });
+    assert!(!parsers.has_failed());
+    assert!(merged.has_failed());
+    assert_eq!(merged.exit_code(), 101);
+}
+
+#[test]
+fn merge_flags_and_run_info() {
+    let a = Conclusion {
+        num_passed: 1,
+        allow_empty: true,
+        run_info: RunInfo {
+            elapsed: Duration::from_secs(1),
+            num_threads: 4,
+            shuffle_seed: None,
+        },
+        ..Default::default()
+    };
+    let b = Conclusion {
+        num_ignored: 1,
+        ignored_is_error: true,
+        run_info: RunInfo {
+            elapsed: Duration::from_secs(2),
+            num_threads: 1,
+            shuffle_seed: Some(7),
+        },
+        ..Default::default()
+    };
+
+    let merged = a.merge(&b);
+    assert!(!merged.allow_empty);
+    assert!(merged.ignored_is_error);
+    assert!(merged.has_failed());
+    assert_eq!(merged.run_info, RunInfo {
+        elapsed: Duration::from_secs(3),
+        num_threads: 4,
+        shuffle_seed: Some(7),
+    });
+}
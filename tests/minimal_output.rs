@@ -0,0 +1,62 @@
+use common::{args, do_run};
+use libtest_mimic::{Failed, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Err(Failed::from("was not a good boy"))),
+        Trial::test("fox", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn default_spacing() {
+    let (_, out) = do_run(args(["--test-threads=1", "cat"]), tests());
+    assert_eq!(out, "\nrunning 1 test\ntest cat ... ok\n\n\
+        test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 2 filtered out; \
+        finished in 0.00s\n\n");
+}
+
+#[test]
+fn pretty() {
+    let (_, out) = do_run(args(["--minimal-output", "--test-threads=1", "cat"]), tests());
+    assert_eq!(out, "test cat ... ok\n\
+        test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 2 filtered out; \
+        finished in 0.00s\n");
+}
+
+#[test]
+fn terse() {
+    let (_, out) = do_run(args(["--minimal-output", "-q", "--test-threads=1", "cat"]), tests());
+    assert_eq!(out, ".\n\
+        test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 2 filtered out; \
+        finished in 0.00s\n");
+}
+
+#[test]
+fn with_failures() {
+    let (_, out) = do_run(args(["--minimal-output", "--test-threads=1"]), tests());
+    assert_log!(out, "
+        test cat ... ok
+        test dog ... FAILED
+        test fox ... ignored
+
+        failures:
+
+        ---- dog stdout ----
+        was not a good boy
+
+
+        failures:
+            dog
+
+        to rerun: <exe> --exact dog
+        test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
@@ -36,11 +36,10 @@ fn tests() -> Vec<Trial> {
 fn normal() {
     check(args([]), tests, 16,
         Conclusion {
-            num_filtered_out: 0,
             num_passed: 4,
             num_failed: 4,
             num_ignored: 8,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test          cat    ... ok
@@ -62,16 +61,16 @@ fn normal() {
 
             failures:
 
-            ---- dog ----
+            ---- dog stdout ----
             was not a good boy
 
-            ---- bunny ----
+            ---- bunny stdout ----
             jumped too high
 
-            ---- blue ----
+            ---- blue stdout ----
             sky fell down
 
-            ---- green ----
+            ---- green stdout ----
             was poisoned
 
 
@@ -80,6 +79,8 @@ fn normal() {
                 bunny
                 blue
                 green
+
+            to rerun: <exe> --exact dog bunny blue green
         ",
     );
 }
@@ -88,11 +89,10 @@ fn normal() {
 fn test_mode() {
     check(args(["--test"]), tests, 16,
         Conclusion {
-            num_filtered_out: 0,
             num_passed: 2,
             num_failed: 2,
             num_ignored: 12,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test          cat    ... ok
@@ -114,16 +114,18 @@ fn test_mode() {
 
             failures:
 
-            ---- dog ----
+            ---- dog stdout ----
             was not a good boy
 
-            ---- bunny ----
+            ---- bunny stdout ----
             jumped too high
 
 
             failures:
                 dog
                 bunny
+
+            to rerun: <exe> --test --exact dog bunny
         ",
     );
 }
@@ -132,11 +134,10 @@ fn test_mode() {
 fn bench_mode() {
     check(args(["--bench"]), tests, 16,
         Conclusion {
-            num_filtered_out: 0,
-            num_passed: 0,
             num_failed: 2,
             num_ignored: 12,
             num_measured: 2,
+            ..Default::default()
         },
         "
             test          cat    ... ignored
@@ -158,20 +159,45 @@ fn bench_mode() {
 
             failures:
 
-            ---- blue ----
+            ---- blue stdout ----
             sky fell down
 
-            ---- green ----
+            ---- green stdout ----
             was poisoned
 
 
             failures:
                 blue
                 green
+
+            to rerun: <exe> --bench --exact blue green
+
+            benchmarks:
+                red     32 ns/iter (+/- 3)
+                yellow  64 ns/iter (+/- 4)
         ",
     );
 }
 
+#[test]
+fn aliases() {
+    let without_time = |out: String| {
+        out.lines()
+            .map(|l| l.split("finished in").next().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let run = |flag| {
+        let mut args = args([flag]);
+        args.test_threads = Some(1);
+        let (c, out) = do_run(args, tests());
+        (c, without_time(out))
+    };
+
+    assert_eq!(run("--no-benches"), run("--test"));
+    assert_eq!(run("--no-tests"), run("--bench"));
+}
+
 #[test]
 fn list() {
     let (c, out) = common::do_run(args(["--list"]), tests());
@@ -193,13 +219,7 @@ fn list() {
         [banana] orange: bench
         [banana] pink: bench
     ");
-    assert_eq!(c, Conclusion {
-        num_filtered_out: 0,
-        num_passed: 0,
-        num_failed: 0,
-        num_ignored: 0,
-        num_measured: 0,
-     });
+    assert_eq!(c, Conclusion { allow_empty: true, ..Default::default() });
 }
 
 #[test]
@@ -215,13 +235,7 @@ fn list_ignored() {
         [banana] orange: bench
         [banana] pink: bench
     ");
-    assert_eq!(c, Conclusion {
-        num_filtered_out: 0,
-        num_passed: 0,
-        num_failed: 0,
-        num_ignored: 0,
-        num_measured: 0,
-     });
+    assert_eq!(c, Conclusion { allow_empty: true, ..Default::default() });
 }
 
 #[test]
@@ -233,13 +247,37 @@ fn list_with_filter() {
         cyan: bench
         [banana] orange: bench
     ");
-    assert_eq!(c, Conclusion {
-        num_filtered_out: 0,
-        num_passed: 0,
-        num_failed: 0,
-        num_ignored: 0,
-        num_measured: 0,
-     });
+    assert_eq!(c, Conclusion { allow_empty: true, ..Default::default() });
+}
+
+#[test]
+fn list_names_only() {
+    let (_, out) = common::do_run(args(["--list", "--names-only", "--ignored", "a"]), tests());
+    assert_log!(out, "
+        bear
+        cyan
+        orange
+    ");
+}
+
+#[test]
+fn list_verbose() {
+    let (_, out) = common::do_run(args(["--list", "--verbose-list", "o"]), tests());
+    assert_log!(out, "
+        dog               test
+        fox     [apple]   test
+        frog              test   (ignored)
+        owl               test   (ignored)
+        yellow  [kiwi]    bench
+        orange  [banana]  bench  (ignored)
+
+        4 tests, 2 benchmarks
+    ");
+
+    let (_, out) = common::do_run(args(["--list", "--verbose-list", "--ignored", "cat"]), tests());
+    assert_log!(out, "
+        0 tests, 0 benchmarks
+    ");
 }
 
 #[test]
@@ -248,9 +286,8 @@ fn filter_c() {
         Conclusion {
             num_filtered_out: 14,
             num_passed: 1,
-            num_failed: 0,
             num_ignored: 1,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test cat  ... ok
@@ -259,6 +296,78 @@ fn filter_c() {
     );
 }
 
+#[test]
+fn multiple_filters() {
+    check(args(["--test", "c", "fo", "bear", "--skip", "cyan"]), tests, 3,
+        Conclusion {
+            num_filtered_out: 13,
+            num_passed: 2,
+            num_ignored: 1,
+            ..Default::default()
+        },
+        "
+            test          cat  ... ok
+            test [apple]  fox  ... ok
+            test [banana] bear ... ignored
+        ",
+    );
+}
+
+#[test]
+fn multiple_filters_exact() {
+    check(args(["--exact", "cat", "fo", "red"]), tests, 2,
+        Conclusion {
+            num_filtered_out: 14,
+            num_passed: 2,
+            ..Default::default()
+        },
+        "
+            test cat ... ok
+            test red ... ok
+        ",
+    );
+}
+
+#[test]
+fn kind() {
+    check(args(["--kind", "banana", "--kind=kiwi", "e"]), tests, 4,
+        Conclusion {
+            num_filtered_out: 12,
+            num_passed: 1,
+            num_failed: 1,
+            num_ignored: 2,
+            ..Default::default()
+        },
+        "
+            test [banana] bear   ... ignored
+            test [kiwi]   yellow ... ok
+            test [kiwi]   green  ... FAILED
+            test [banana] orange ... ignored
+
+            failures:
+
+            ---- green stdout ----
+            was poisoned
+
+
+            failures:
+                green
+
+            to rerun: <exe> --kind=banana --kind=kiwi --exact green
+        ",
+    );
+}
+
+#[test]
+fn empty_kind() {
+    let (c, _) = do_run(args(["--kind", "", "--test"]), tests());
+    assert_eq!(c.num_filtered_out, 8);
+    assert_eq!(c.num_passed + c.num_failed + c.num_ignored, 8);
+
+    let (c, _) = do_run(args(["--kind", "apple"]), tests());
+    assert_eq!(c.num_filtered_out, 14);
+}
+
 #[test]
 fn filter_o_test() {
     check(args(["--test", "o"]), tests, 6,
@@ -267,7 +376,7 @@ fn filter_o_test() {
             num_passed: 1,
             num_failed: 1,
             num_ignored: 4,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test          dog    ... FAILED
@@ -279,12 +388,14 @@ fn filter_o_test() {
 
             failures:
 
-            ---- dog ----
+            ---- dog stdout ----
             was not a good boy
 
 
             failures:
                 dog
+
+            to rerun: <exe> --test --exact dog
         ",
     );
 }
@@ -297,7 +408,7 @@ fn filter_o_test_include_ignored() {
             num_passed: 2,
             num_failed: 2,
             num_ignored: 2,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test          dog    ... FAILED
@@ -309,16 +420,18 @@ fn filter_o_test_include_ignored() {
 
             failures:
 
-            ---- dog ----
+            ---- dog stdout ----
             was not a good boy
 
-            ---- owl ----
+            ---- owl stdout ----
             broke neck
 
 
             failures:
                 dog
                 owl
+
+            to rerun: <exe> --include-ignored --test --exact dog owl
         ",
     );
 }
@@ -331,7 +444,7 @@ fn filter_o_test_ignored() {
             num_passed: 1,
             num_failed: 1,
             num_ignored: 1,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test          frog   ... ok
@@ -340,12 +453,14 @@ fn filter_o_test_ignored() {
 
             failures:
 
-            ---- owl ----
+            ---- owl stdout ----
             broke neck
 
 
             failures:
                 owl
+
+            to rerun: <exe> --ignored --test --exact owl
         ",
     );
 }
@@ -354,11 +469,9 @@ fn filter_o_test_ignored() {
 fn normal_include_ignored() {
     check(args(["--include-ignored"]), tests, 16,
         Conclusion {
-            num_filtered_out: 0,
             num_passed: 8,
             num_failed: 8,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test          cat    ... ok
@@ -380,28 +493,28 @@ fn normal_include_ignored() {
 
             failures:
 
-            ---- dog ----
+            ---- dog stdout ----
             was not a good boy
 
-            ---- bunny ----
+            ---- bunny stdout ----
             jumped too high
 
-            ---- owl ----
+            ---- owl stdout ----
             broke neck
 
-            ---- bear ----
+            ---- bear stdout ----
             no honey
 
-            ---- blue ----
+            ---- blue stdout ----
             sky fell down
 
-            ---- green ----
+            ---- green stdout ----
             was poisoned
 
-            ---- cyan ----
+            ---- cyan stdout ----
             not creative enough
 
-            ---- pink ----
+            ---- pink stdout ----
             bad
 
 
@@ -414,6 +527,8 @@ fn normal_include_ignored() {
                 green
                 cyan
                 pink
+
+            to rerun: <exe> --include-ignored --exact dog bunny owl bear blue green cyan pink
         ",
     );
 }
@@ -425,8 +540,7 @@ fn normal_ignored() {
             num_filtered_out: 8,
             num_passed: 4,
             num_failed: 4,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test          frog   ... ok
@@ -440,16 +554,16 @@ fn normal_ignored() {
 
             failures:
 
-            ---- owl ----
+            ---- owl stdout ----
             broke neck
 
-            ---- bear ----
+            ---- bear stdout ----
             no honey
 
-            ---- cyan ----
+            ---- cyan stdout ----
             not creative enough
 
-            ---- pink ----
+            ---- pink stdout ----
             bad
 
 
@@ -458,6 +572,8 @@ fn normal_ignored() {
                 bear
                 cyan
                 pink
+
+            to rerun: <exe> --ignored --exact owl bear cyan pink
         ",
     );
 }
@@ -470,7 +586,7 @@ fn lots_of_flags() {
             num_passed: 1,
             num_failed: 1,
             num_ignored: 1,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test [apple] fox    ... ok
@@ -479,12 +595,14 @@ fn lots_of_flags() {
 
             failures:
 
-            ---- owl ----
+            ---- owl stdout ----
             broke neck
 
 
             failures:
                 owl
+
+            to rerun: <exe> --include-ignored --test --exact owl
         ",
     );
 }
@@ -493,27 +611,26 @@ fn lots_of_flags() {
 fn terse_output() {
     let (c, out) = do_run(args(["--format", "terse", "--test-threads", "1"]), tests());
     assert_eq!(c, Conclusion {
-        num_filtered_out: 0,
         num_passed: 4,
         num_failed: 4,
         num_ignored: 8,
-        num_measured: 0,
+        ..Default::default()
     });
     assert_log!(out, "
         running 16 tests
         .F.Fiiii.F.Fiiii
         failures:
 
-        ---- dog ----
+        ---- dog stdout ----
         was not a good boy
 
-        ---- bunny ----
+        ---- bunny stdout ----
         jumped too high
 
-        ---- blue ----
+        ---- blue stdout ----
         sky fell down
 
-        ---- green ----
+        ---- green stdout ----
         was poisoned
 
 
@@ -523,7 +640,128 @@ fn terse_output() {
             blue
             green
 
+        to rerun: <exe> --exact dog bunny blue green
+
         test result: FAILED. 4 passed; 4 failed; 8 ignored; 0 measured; 0 filtered out; \
             finished in 0.00s
     ");
 }
+
+#[test]
+fn terse_bench_output() {
+    let args = args(["--format", "terse", "--bench", "--skip", "u", "--skip", "n", "e"]);
+    let (c, out) = do_run(args, tests());
+    assert_eq!(c.num_measured, 2);
+    assert_log!(out, "
+        running 3 tests
+        ibb
+        benchmarks:
+            red     32 ns/iter (+/- 3)
+            yellow  64 ns/iter (+/- 4)
+
+        test result: ok. 0 passed; 0 failed; 1 ignored; 2 measured; 13 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn conclusion_to_json() {
+    let (c, _) = do_run(args(["--test", "-q", "o"]), tests());
+    assert_eq!(
+        c.to_json(),
+        r#"{"passed":1,"failed":1,"ignored":4,"measured":0,"filtered_out":10}"#,
+    );
+}
+
+#[test]
+fn list_json_array() {
+    let (_, out) = do_run(args(["--list", "--json-array"]), tests());
+    let list: serde_json::Value = serde_json::from_str(&out).unwrap();
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 16);
+    assert_eq!(list[2], serde_json::json!({
+        "type": "test",
+        "name": "fox",
+        "kind": "apple",
+        "is_ignored": false,
+        "is_bench": false,
+    }));
+    assert_eq!(list[4]["is_ignored"], true);
+    assert_eq!(list[8]["is_bench"], true);
+
+    let (_, out) = do_run(args(["--list", "--json-array", "--ignored", "o"]), tests());
+    let list: serde_json::Value = serde_json::from_str(&out).unwrap();
+    let names = list.as_array().unwrap().iter()
+        .map(|entry| entry["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["frog", "owl", "orange"]);
+}
+
+#[test]
+fn list_json() {
+    let (c, out) = do_run(args(["--list", "--format=json", "--skip", "a"]), tests());
+    assert_eq!(c.num_passed + c.num_failed + c.num_filtered_out, 0);
+    assert_log!(out, r#"
+        { "type": "test", "name": "dog", "kind": "", "is_ignored": false, "is_bench": false }
+        { "type": "test", "name": "fox", "kind": "apple", "is_ignored": false, "is_bench": false }
+        { "type": "test", "name": "bunny", "kind": "apple", "is_ignored": false, "is_bench": false }
+        { "type": "test", "name": "frog", "kind": "", "is_ignored": true, "is_bench": false }
+        { "type": "test", "name": "owl", "kind": "", "is_ignored": true, "is_bench": false }
+        { "type": "test", "name": "fly", "kind": "banana", "is_ignored": true, "is_bench": false }
+        { "type": "test", "name": "red", "kind": "", "is_ignored": false, "is_bench": true }
+        { "type": "test", "name": "blue", "kind": "", "is_ignored": false, "is_bench": true }
+        { "type": "test", "name": "yellow", "kind": "kiwi", "is_ignored": false, "is_bench": true }
+        { "type": "test", "name": "green", "kind": "kiwi", "is_ignored": false, "is_bench": true }
+        { "type": "test", "name": "purple", "kind": "", "is_ignored": true, "is_bench": true }
+        { "type": "test", "name": "pink", "kind": "banana", "is_ignored": true, "is_bench": true }
+    "#);
+}
+
+#[test]
+fn bench_table_sorted_by_name() {
+    let bench = |name: &str, avg| Trial::bench(name, move |_| {
+        Ok(Some(Measurement { avg, variance: avg / 10 }))
+    });
+    let tests = vec![bench("zebra", 7), bench("ant", 1_234_567), bench("moose", 890)];
+    let (_, out) = do_run(args(["--bench", "--format", "terse"]), tests);
+    assert_log!(out, "
+        running 3 tests
+        bbb
+        benchmarks:
+            ant    1,234,567 ns/iter (+/- 123,456)
+            moose        890 ns/iter (+/- 89)
+            zebra          7 ns/iter (+/- 0)
+
+        test result: ok. 0 passed; 0 failed; 0 ignored; 3 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn rerun_hint_quotes_and_caps_names() {
+    let tests = [Trial::test("don't", || Err("nope".into()))].into_iter()
+        .chain((0..12).map(|i| Trial::test(format!("fails {i}"), || Err("nope".into()))))
+        .collect();
+    let (_, out) = do_run(args(["--test-threads=1"]), tests);
+    assert!(out.contains(
+        "to rerun: <exe> --exact 'don'\\''t' 'fails 0' 'fails 1' 'fails 2' \
+            'fails 3' 'fails 4' 'fails 5' 'fails 6' 'fails 7' 'fails 8' (and 3 more)\n",
+    ), "{}", out);
+}
+
+#[test]
+fn rerun_hint_repeats_selection_flags() {
+    let tests = vec![
+        Trial::test("cat", || Err("nope".into())).with_tag("slow").with_tag("net work"),
+        Trial::test("dog", || Err("nope".into())).with_tag("flaky"),
+    ];
+    let flags = [
+        "--test-threads=1", "--tag=slow", "--tag=net work", "--all-tags", "--exclude-tag=flaky",
+        "--exclude-should-panic", "--skip=dog", "c",
+    ];
+    let (_, out) = do_run(args(flags), tests);
+    assert!(out.contains(
+        "to rerun: <exe> --exclude-should-panic --all-tags --tag=slow '--tag=net work' \
+            --exclude-tag=flaky --exact cat\n",
+    ), "{}", out);
+}
@@ -0,0 +1,35 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+fn checks() -> Vec<Trial> {
+    vec![
+        Trial::test("schema_is_valid", || Ok(())),
+        Trial::test("links_resolve", || Err("broken link".into())),
+    ]
+}
+
+#[test]
+fn custom_noun() {
+    let args = args(["--test-threads", "1"]).with_noun("check", "checks");
+    let (c, out) = do_run(args, checks());
+    assert_eq!(c.num_failed, 1);
+
+    let lines = out.trim().lines().take(3).collect::<Vec<_>>().join("\n");
+    assert_log!(lines, "
+        running 2 checks
+        check schema_is_valid ... ok
+        check links_resolve   ... FAILED
+    ");
+    assert!(out.contains("\ncheck result: FAILED. 1 passed; 1 failed;"), "{}", out);
+    assert!(!out.contains("test"), "{}", out);
+}
+
+#[test]
+fn custom_noun_singular() {
+    let (_, out) = do_run(args(["links"]).with_noun("check", "checks"), checks());
+    assert!(out.trim().starts_with("running 1 check\n"), "{}", out);
+}
@@ -0,0 +1,63 @@
+use std::{thread, time::Duration};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+/// Tests passed in unsorted order, where the tests sorting first take the
+/// longest, so that they finish last when run in parallel.
+fn tests() -> Vec<Trial> {
+    let sleepy = |name: &str, millis| {
+        Trial::test(name, move || {
+            thread::sleep(Duration::from_millis(millis));
+            Ok(())
+        })
+    };
+
+    vec![
+        sleepy("d", 0).with_kind("b"),
+        sleepy("c", 20),
+        sleepy("a", 120),
+        sleepy("b", 80),
+        Trial::test("e", || Err("oops".into())).with_kind("a"),
+        sleepy("f", 0).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn parallel() {
+    let (c, out) = do_run(args(["--ensure-ordered"]), tests());
+    assert_eq!(c.num_passed, 4);
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(c.num_ignored, 1);
+
+    let out = out.lines().skip(2).take(6).collect::<Vec<_>>().join("\n");
+    assert_log!(out, "
+        test     a ... ok
+        test     b ... ok
+        test     c ... ok
+        test     f ... ignored
+        test [a] e ... FAILED
+        test [b] d ... ok
+    ");
+}
+
+#[test]
+fn main_thread() {
+    let mut args = args(["--ensure-ordered"]);
+    args.test_threads = Some(1);
+    let (_, out) = do_run(args, tests());
+
+    let out = out.lines().skip(2).take(6).collect::<Vec<_>>().join("\n");
+    assert_log!(out, "
+        test     a ... ok
+        test     b ... ok
+        test     c ... ok
+        test     f ... ignored
+        test [a] e ... FAILED
+        test [b] d ... ok
+    ");
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "otlp")]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use serde_json::Value;
+
+#[macro_use]
+mod common;
+
+
+/// Accepts a single request and returns its body, acting like an OTLP/HTTP
+/// collector that keeps everything in memory.
+fn collector() -> (String, thread::JoinHandle<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(len) = line.strip_prefix("Content-Length: ") {
+                content_length = len.trim().parse().unwrap();
+            }
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader.get_mut().write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").unwrap();
+        (request_line, String::from_utf8(body).unwrap())
+    });
+
+    (endpoint, handle)
+}
+
+#[test]
+fn one_span_per_test() {
+    let (endpoint, collector) = collector();
+    let (c, out) = do_run(args(["--otlp-endpoint", &endpoint]), vec![
+        Trial::test("passes", || Ok(())).with_kind("unit"),
+        Trial::test("fails", || Err("nope".into())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]);
+    assert_eq!(c.num_failed, 1);
+    assert!(!out.contains("warning"), "{}", out);
+
+    let (request_line, body) = collector.join().unwrap();
+    assert_eq!(request_line, "POST /v1/traces HTTP/1.1\r\n");
+
+    let body: Value = serde_json::from_str(&body).unwrap();
+    let spans = body["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap();
+    assert_eq!(spans.len(), 3);
+
+    let span = |name: &str| spans.iter().find(|s| s["name"] == name).unwrap();
+    let attribute = |span: &Value, key: &str| {
+        span["attributes"].as_array().unwrap().iter()
+            .find(|a| a["key"] == key)
+            .map(|a| a["value"]["stringValue"].clone())
+            .unwrap()
+    };
+
+    assert_eq!(span("passes")["status"]["code"], 1);
+    assert_eq!(attribute(span("passes"), "test.kind"), "unit");
+    assert_eq!(attribute(span("passes"), "test.outcome"), "passed");
+    assert_eq!(span("fails")["status"]["code"], 2);
+    assert_eq!(span("fails")["status"]["message"], "nope");
+    assert_eq!(attribute(span("ignored"), "test.outcome"), "ignored");
+    assert_eq!(span("ignored")["status"]["code"], 0);
+
+    assert!(spans.iter().all(|s| s["traceId"] == spans[0]["traceId"]));
+    assert_eq!(spans[0]["traceId"].as_str().unwrap().len(), 32);
+    for s in spans {
+        let start: u128 = s["startTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        let end: u128 = s["endTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        assert!(start <= end);
+    }
+}
+
+#[test]
+fn unreachable_collector_only_warns() {
+    // Bind and drop a listener to get a port nobody listens on.
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let endpoint = format!("http://127.0.0.1:{}", port);
+    let (c, out) = do_run(args(["--otlp-endpoint", &endpoint]), vec![
+        Trial::test("passes", || Ok(())),
+    ]);
+    assert_eq!(c.num_passed, 1);
+    assert!(out.contains(&format!("warning: failed to export spans to {}", endpoint)), "{}", out);
+}
@@ -0,0 +1,119 @@
+use std::{fmt, io};
+
+use common::{args, check};
+use libtest_mimic::{mimic_assert_eq, Trial, Conclusion, Failed, Outcome};
+
+#[macro_use]
+mod common;
+
+
+#[derive(Debug)]
+struct Outer(io::Error);
+
+impl fmt::Display for Outer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("could not load config")
+    }
+}
+
+impl std::error::Error for Outer {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+fn message(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::Failed(failed) => failed.message().unwrap().to_owned(),
+        other => panic!("unexpected outcome {:?}", other),
+    }
+}
+
+#[test]
+fn constructors() {
+    assert_eq!(message(Outcome::failed(3)), "3");
+    assert_eq!(message(Outcome::failed("bad")), "bad");
+
+    let err = Outer(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+    assert_eq!(
+        message(Outcome::fail_with(&err)),
+        "could not load config\ncaused by: no such file",
+    );
+}
+
+#[test]
+fn runner_returning_result() {
+    let tests = || vec![
+        Trial::test_result("passes", || Ok::<_, io::Error>(())),
+        Trial::test_result("fails", || -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::Other, "disk full"))?;
+            Ok(())
+        }),
+        Trial::test_result("custom", || Err("custom error")),
+    ];
+
+    check(args([]), tests, 3,
+        Conclusion {
+            num_passed: 1,
+            num_failed: 2,
+            ..Default::default()
+        },
+        "
+            test passes ... ok
+            test fails  ... FAILED
+            test custom ... FAILED
+
+            failures:
+
+            ---- fails stdout ----
+            disk full
+
+            ---- custom stdout ----
+            custom error
+
+
+            failures:
+                fails
+                custom
+
+            to rerun: <exe> --exact fails custom
+        ",
+    );
+}
+
+#[test]
+fn assert_eq_macro() {
+    fn compare(a: &str, b: &str) -> Result<(), Failed> {
+        mimic_assert_eq!(a.len(), 3);
+        mimic_assert_eq!(a, b, "comparing {}", 2);
+        Ok(())
+    }
+
+    assert!(compare("cat", "cat").is_ok());
+
+    let failed = compare("horse", "cat").unwrap_err();
+    assert_eq!(failed.mismatched_values(), Some(("5", "3")));
+    assert_eq!(failed.message(), Some("assertion `left == right` failed\n  left: 5\n right: 3"));
+
+    let failed = compare("cat", "dog").unwrap_err();
+    assert_eq!(failed.mismatched_values(), Some((r#""cat""#, r#""dog""#)));
+    assert_eq!(
+        failed.message(),
+        Some("comparing 2: assertion `left == right` failed\n  left: \"cat\"\n right: \"dog\""),
+    );
+}
+
+#[test]
+fn assert_eq_macro_colored() {
+    let tests = vec![Trial::test("vec", || {
+        mimic_assert_eq!(vec![1, 2], [1, 3]);
+        Ok(())
+    })];
+
+    let (c, out) = common::do_run(args(["--color=always"]), tests);
+    assert_eq!(c.num_failed, 1);
+    assert!(out.contains(
+        "assertion `left == right` failed\n  left: \u{1b}[0m\u{1b}[31m[1, 2]\u{1b}[0m\n \
+            right: \u{1b}[0m\u{1b}[32m[1, 3]\u{1b}[0m\n",
+    ), "{:?}", out);
+}
@@ -16,11 +16,9 @@ fn tests() -> Vec<Trial> {
 fn normal() {
     check(args([]), tests, 2,
         Conclusion {
-            num_filtered_out: 0,
             num_passed: 1,
             num_failed: 1,
-            num_ignored: 0,
-            num_measured: 0,
+            ..Default::default()
         },
         "
             test passes ... ok
@@ -28,12 +26,14 @@ fn normal() {
 
             failures:
 
-            ---- panics ----
+            ---- panics stdout ----
             test panicked: uh oh
 
 
             failures:
                 panics
+
+            to rerun: <exe> --exact panics
         "
     );
 }
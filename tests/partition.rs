@@ -0,0 +1,90 @@
+use std::{fs, path::Path};
+
+use common::{args, check, do_run, TEMPDIR};
+use libtest_mimic::{Trial, Conclusion, Partition};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    ["alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta"].iter()
+        .map(|name| Trial::test(*name, || Ok(())))
+        .collect()
+}
+
+#[test]
+fn parse() {
+    assert_eq!("count:1/3".parse(), Ok(Partition::Count { index: 1, total: 3 }));
+    assert_eq!("hash:3/3".parse(), Ok(Partition::Hash { index: 3, total: 3 }));
+    assert!("count:0/3".parse::<Partition>().is_err());
+    assert!("hash:4/3".parse::<Partition>().is_err());
+    assert!("slice:1/3".parse::<Partition>().is_err());
+    assert!("count:1".parse::<Partition>().is_err());
+}
+
+#[test]
+fn count() {
+    check(args(["--partition", "count:2/3"]), tests, 2,
+        Conclusion {
+            num_filtered_out: 5,
+            num_passed: 2,
+            ..Default::default()
+        },
+        "
+            test delta   ... ok
+            test epsilon ... ok
+        ",
+    );
+}
+
+#[test]
+fn count_after_skip() {
+    check(args(["--partition", "count:1/2", "--skip", "eta"]), tests, 2,
+        Conclusion {
+            num_filtered_out: 5,
+            num_passed: 2,
+            ..Default::default()
+        },
+        "
+            test alpha ... ok
+            test gamma ... ok
+        ",
+    );
+}
+
+#[test]
+fn hash_partitions_are_disjoint_and_complete() {
+    let mut all = Vec::new();
+    for i in 1..=3 {
+        let partition = format!("hash:{i}/3");
+        let mut args = args(["--partition", &partition]);
+        args.test_threads = Some(1);
+        let (c, out) = do_run(args, tests());
+        assert_eq!(c.num_passed + c.num_filtered_out, 7);
+        all.extend(
+            out.lines()
+                .filter(|l| l.contains(" ... "))
+                .filter_map(|l| l.strip_prefix("test "))
+                .filter_map(|l| l.split_whitespace().next())
+                .map(|s| s.to_string()),
+        );
+    }
+
+    all.sort();
+    assert_eq!(all, ["alpha", "beta", "delta", "epsilon", "eta", "gamma", "zeta"]);
+}
+
+#[test]
+fn report_filtered() {
+    let path = Path::new(TEMPDIR).join("libtest_mimic_report_filtered.txt");
+    let path = path.to_str().unwrap();
+    let args = args(["--report-filtered", path, "--skip", "eta", "--partition", "count:1/2", "a"]);
+    let (c, _) = do_run(args, tests());
+    assert_eq!(c.num_filtered_out, 5);
+
+    // First the tests removed by the filter and `--skip`, then the tests of
+    // other partitions.
+    assert_eq!(fs::read_to_string(path).unwrap(), "beta\nepsilon\nzeta\neta\ndelta\n");
+}
@@ -0,0 +1,45 @@
+use common::{args, TEMPDIR};
+use libtest_mimic::{run_with_plan, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+#[test]
+fn reorder_and_ignore() {
+    let path = std::path::Path::new(TEMPDIR).join("libtest_mimic_plan_output.txt");
+    let mut args = args(["--skip", "skipped", "--logfile", path.to_str().unwrap()]);
+    args.test_threads = Some(1);
+
+    let tests = vec![
+        Trial::test("first", || Ok(())),
+        Trial::test("second", || Err("should be ignored".into())),
+        Trial::test("skipped", || Ok(())),
+        Trial::test("third", || Ok(())),
+    ];
+    let c = run_with_plan(&args, tests, |mut tests| {
+        assert_eq!(tests.len(), 3);
+        tests.reverse();
+        tests.into_iter()
+            .map(|t| if t.name() == "second" { t.with_ignored_flag(true) } else { t })
+            .chain([Trial::test("injected_long_name", || Ok(()))])
+            .collect()
+    });
+
+    let out = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(c.num_passed, 3);
+    assert_eq!(c.num_ignored, 1);
+    assert_eq!(c.num_failed, 0);
+    assert_eq!(c.num_filtered_out, 1);
+
+    let lines = out.trim().lines().skip(1).take(4).collect::<Vec<_>>().join("\n");
+    assert_log!(lines, "
+        test third              ... ok
+        test second             ... ignored
+        test first              ... ok
+        test injected_long_name ... ok
+    ");
+}
@@ -0,0 +1,40 @@
+use std::thread;
+
+use common::args;
+use libtest_mimic::{threadpool, Trial};
+
+#[macro_use]
+mod common;
+
+
+#[test]
+fn runs_on_given_pool() {
+    let pool = threadpool::Builder::new()
+        .num_threads(3)
+        .thread_name("custom-pool".into())
+        .build();
+    let tests = (0..10)
+        .map(|i| Trial::test(format!("t{i}"), || {
+            assert_eq!(thread::current().name(), Some("custom-pool"));
+            Ok(())
+        }))
+        .collect();
+
+    let c = libtest_mimic::run_on_pool(&args(["--test-threads=1"]), tests, &pool);
+    assert_eq!(c.num_passed, 10);
+    assert_eq!(c.run_info.num_threads, 3);
+}
+
+#[test]
+fn single_thread_pool_is_not_main_thread() {
+    let pool = threadpool::ThreadPool::new(1);
+    let outer_thread = thread::current().id();
+    let tests = vec![Trial::test("check", move || {
+        assert_ne!(outer_thread, thread::current().id());
+        Ok(())
+    })];
+
+    let c = libtest_mimic::run_on_pool(&args([]), tests, &pool);
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.run_info.num_threads, 1);
+}
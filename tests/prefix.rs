@@ -0,0 +1,42 @@
+use common::run_names;
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("parser::json::nested", || Ok(())),
+        Trial::test("parser::json", || Ok(())),
+        Trial::test("parser::json5::nested", || Ok(())),
+        Trial::test("xparser::json::nested", || Ok(())),
+        Trial::test("Parser::JSON::upper", || Ok(())),
+    ]
+}
+
+#[test]
+fn prefix() {
+    let expected = ["parser::json::nested", "parser::json"];
+    assert_eq!(run_names(["--prefix", "parser::json"], tests()), expected);
+    assert_eq!(run_names(["--prefix", "parser::json::"], tests()), expected);
+    assert_eq!(run_names(["--prefix", "parser::js"], tests()), Vec::<String>::new());
+    assert_eq!(
+        run_names(["--prefix", "--skip", "parser::json", "parser"], tests()),
+        ["parser::json5::nested"],
+    );
+}
+
+#[test]
+fn prefix_ignore_case() {
+    assert_eq!(
+        run_names(["--prefix", "--ignore-case", "PARSER::json"], tests()),
+        ["parser::json::nested", "parser::json", "Parser::JSON::upper"],
+    );
+}
+
+#[test]
+fn substring_matches_mid_path() {
+    assert_eq!(run_names(["parser::json"], tests()).len(), 4);
+}
@@ -0,0 +1,38 @@
+use common::args;
+use libtest_mimic::{run_with_progress, Trial};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("a", || Ok(())),
+        Trial::test("b", || Err("nope".into())),
+        Trial::test("c", || Ok(())).with_ignored_flag(true),
+        Trial::test("d", || Ok(())),
+        Trial::test("skipped", || Ok(())),
+    ]
+}
+
+#[test]
+fn called_for_every_finished_trial() {
+    for threads in ["1", "3"] {
+        let args = args(["--test-threads", threads, "--logfile", "/dev/null", "--skip=skipped"]);
+        let mut calls = Vec::new();
+        let c = run_with_progress(&args, tests(), |completed, total| {
+            calls.push((completed, total));
+        });
+        assert_eq!(c.num_filtered_out, 1);
+        assert_eq!(calls, [(1, 4), (2, 4), (3, 4), (4, 4)]);
+    }
+}
+
+#[test]
+fn not_called_for_list() {
+    let mut called = false;
+    let _ = run_with_progress(&args(["--list", "--logfile", "/dev/null"]), tests(), |_, _| {
+        called = true;
+    });
+    assert!(!called);
+}
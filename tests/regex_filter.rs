@@ -0,0 +1,48 @@
+use common::{args, check, do_run};
+use libtest_mimic::{Trial, Conclusion};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("parse_ascii_utf8", || Ok(())),
+        Trial::test("parse_emoji_utf8", || Ok(())),
+        Trial::test("parse_utf16", || Ok(())),
+        Trial::test("print_ascii_utf8", || Ok(())),
+    ]
+}
+
+#[test]
+fn filter() {
+    check(args(["--filter-regex", "parse_.*_utf8"]), tests, 2,
+        Conclusion {
+            num_filtered_out: 2,
+            num_passed: 2,
+            ..Default::default()
+        },
+        "
+            test parse_ascii_utf8 ... ok
+            test parse_emoji_utf8 ... ok
+        ",
+    );
+}
+
+#[test]
+fn skip() {
+    check(args(["--filter-regex", "--skip", "^parse_[a-z]+_", "--skip", "16$"]), tests, 1,
+        Conclusion {
+            num_filtered_out: 3,
+            num_passed: 1,
+            ..Default::default()
+        },
+        "test print_ascii_utf8 ... ok",
+    );
+}
+
+#[test]
+fn not_regex_by_default() {
+    let (c, _) = do_run(args(["parse_.*_utf8"]), tests());
+    assert_eq!(c.num_filtered_out, 4);
+}
@@ -0,0 +1,65 @@
+use std::{io::Read, net::TcpListener, thread};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("broke \"badly\"\nreally".into())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn events_are_streamed() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let receiver = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut events = String::new();
+        stream.read_to_string(&mut events).unwrap();
+        events
+    });
+
+    let mut args = args(["--report-socket", &addr]);
+    args.test_threads = Some(1);
+    let (c, out) = do_run(args, tests());
+    assert_eq!(c.num_failed, 1);
+    assert!(out.contains("test passes  ... ok"));
+
+    let events = receiver.join().unwrap();
+    let events = events.lines().collect::<Vec<_>>();
+    assert_eq!(&events[..7], [
+        r#"{ "type": "suite", "event": "started", "test_count": 3 }"#,
+        r#"{ "type": "test", "event": "started", "name": "passes" }"#,
+        r#"{ "type": "test", "name": "passes", "event": "ok" }"#,
+        r#"{ "type": "test", "event": "started", "name": "fails" }"#,
+        concat!(
+            r#"{ "type": "test", "name": "fails", "event": "failed", "#,
+            r#""stdout": "broke \"badly\"\nreally" }"#,
+        ),
+        r#"{ "type": "test", "event": "started", "name": "ignored" }"#,
+        r#"{ "type": "test", "name": "ignored", "event": "ignored" }"#,
+    ]);
+    assert!(events[7].starts_with(concat!(
+        r#"{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "#,
+        r#""measured": 0, "filtered_out": 0, "exec_time": "#,
+    )));
+    assert_eq!(events.len(), 8);
+}
+
+#[test]
+fn connection_failure_is_not_fatal() {
+    // Bind and immediately drop a listener to get a port nobody listens on.
+    let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+
+    let (c, out) = do_run(args(["--report-socket", &addr]), tests());
+    assert_eq!(c.num_passed, 1);
+    assert!(out.starts_with(&format!("warning: failed to connect to report socket {addr}: ")));
+}
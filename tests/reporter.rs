@@ -0,0 +1,116 @@
+use common::args;
+use libtest_mimic::{run_with_reporter, Conclusion, Outcome, Reporter, Trial, TrialResult};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+/// Records all events as strings.
+struct Recorder<'a>(&'a mut Vec<String>);
+
+impl Reporter for Recorder<'_> {
+    fn suite_started(&mut self, num_tests: u64) {
+        self.0.push(format!("suite started: {}", num_tests));
+    }
+
+    fn test_started(&mut self, name: &str, kind: &str) {
+        self.0.push(format!("started: [{}] {}", kind, name));
+    }
+
+    fn test_finished(&mut self, result: &TrialResult) {
+        let outcome = match result.outcome {
+            Outcome::Passed { .. } => "passed",
+            Outcome::Failed(_) => "failed",
+            Outcome::Ignored => "ignored",
+            Outcome::Measured(_) => "measured",
+            Outcome::NotRun => "not run",
+        };
+        self.0.push(format!("finished: {} {}", result.name, outcome));
+    }
+
+    fn suite_finished(&mut self, conclusion: &Conclusion) {
+        let event = format!("suite finished: {} failed", conclusion.num_failed);
+        self.0.push(event);
+    }
+
+    fn warning(&mut self, msg: &str) {
+        self.0.push(format!("warning: {}", msg));
+    }
+}
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Err("was not a good boy".into())).with_kind("pet"),
+        Trial::test("fox", || Ok(())).with_ignored_flag(true),
+        Trial::test("bunny", || Ok(())),
+    ]
+}
+
+#[test]
+fn events_in_main_thread() {
+    let mut events = Vec::new();
+    let args = args(["--test-threads=1", "--skip=bunny"]);
+    let c = run_with_reporter(&args, tests(), Recorder(&mut events));
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(events, [
+        "suite started: 3",
+        "started: [] cat",
+        "finished: cat passed",
+        "started: [pet] dog",
+        "finished: dog failed",
+        "finished: fox ignored",
+        "suite finished: 1 failed",
+    ]);
+}
+
+#[test]
+fn events_in_thread_pool() {
+    let mut events = Vec::new();
+    let c = run_with_reporter(&args(["--test-threads=2"]), tests(), Recorder(&mut events));
+    assert_eq!(c.num_passed, 2);
+
+    // The order depends on the threads, but every test that ran has to be
+    // started before it finished.
+    assert_eq!(events.len(), 9);
+    assert_eq!(events[0], "suite started: 4");
+    assert_eq!(events[8], "suite finished: 1 failed");
+    for name in ["cat", "dog", "bunny"] {
+        let started = events.iter().position(|e| e.starts_with("started") && e.ends_with(name));
+        let finished = events.iter().position(|e| e.starts_with(&format!("finished: {name} ")));
+        assert!(started.unwrap() < finished.unwrap());
+    }
+}
+
+#[test]
+fn warnings_in_machine_formats() {
+    for format in ["json", "junit"] {
+        let mut events = Vec::new();
+        let flags = ["--format", format, "--shuffle", "--allow-empty", "nothing"];
+        let c = run_with_reporter(&args(flags), tests(), Recorder(&mut events));
+        assert_eq!(c.exit_code(), 0);
+        assert_eq!(events, [
+            "suite started: 0",
+            "suite finished: 0 failed",
+        ]);
+
+        let mut events = Vec::new();
+        let flags = ["--format", format, "nothing"];
+        let c = run_with_reporter(&args(flags), tests(), Recorder(&mut events));
+        assert_eq!(c.exit_code(), 2);
+        assert_eq!(events, [
+            "suite started: 0",
+            "warning: no tests to run",
+            "suite finished: 0 failed",
+        ]);
+    }
+}
+
+#[test]
+fn default_methods() {
+    struct Nothing;
+    impl Reporter for Nothing {}
+    let c = run_with_reporter(&args([]), tests(), Nothing);
+    assert_eq!(c.num_passed, 2);
+}
@@ -0,0 +1,89 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{args, do_run};
+use libtest_mimic::{run, Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+type Intervals = Arc<Mutex<Vec<(&'static str, Instant, Instant)>>>;
+
+fn trial(intervals: &Intervals, name: &'static str) -> Trial {
+    let intervals = intervals.clone();
+    Trial::test(name, move || {
+        let start = Instant::now();
+        thread::sleep(Duration::from_millis(100));
+        intervals.lock().unwrap().push((name, start, Instant::now()));
+        Ok(())
+    })
+}
+
+fn overlap(a: &(&str, Instant, Instant), b: &(&str, Instant, Instant)) -> bool {
+    a.1 < b.2 && b.1 < a.2
+}
+
+#[test]
+fn trials_sharing_a_resource_never_overlap() {
+    let intervals = Intervals::default();
+    let tests = vec![
+        trial(&intervals, "gpu_1").with_resource("gpu"),
+        trial(&intervals, "gpu_2").with_resource("gpu"),
+        trial(&intervals, "gpu_3").with_resource("gpu"),
+        trial(&intervals, "free_1"),
+        trial(&intervals, "port_1").with_resource("port"),
+    ];
+    assert_eq!(tests[0].resource(), Some("gpu"));
+    assert_eq!(tests[3].resource(), None);
+
+    let c = run(&args(["--test-threads=5", "--logfile=/dev/null"]), tests);
+    assert_eq!(c.num_passed, 5);
+
+    let intervals = intervals.lock().unwrap();
+    let gpu = intervals.iter().filter(|i| i.0.starts_with("gpu")).collect::<Vec<_>>();
+    for (i, a) in gpu.iter().enumerate() {
+        for b in &gpu[i + 1..] {
+            assert!(!overlap(a, b), "{} and {} overlap", a.0, b.0);
+        }
+    }
+
+    // Trials not using the resource still run in parallel.
+    for name in ["free_1", "port_1"] {
+        let other = intervals.iter().find(|i| i.0 == name).unwrap();
+        assert!(gpu.iter().any(|g| overlap(g, other)), "{} did not run in parallel", name);
+    }
+}
+
+#[test]
+fn waiting_for_a_resource_does_not_count_towards_timeout() {
+    let tests = ["db_1", "db_2", "db_3"].iter()
+        .map(|name| Trial::test(*name, || {
+            thread::sleep(Duration::from_millis(300));
+            Ok(())
+        }).with_resource("db"))
+        .collect();
+
+    // Together, the trials take longer than the timeout.
+    let (c, out) = do_run(args(["--test-threads=3", "--test-timeout=0.5"]), tests);
+    assert_eq!(c, Conclusion { num_passed: 3, ..Default::default() }, "{}", out);
+}
+
+#[test]
+fn waiting_trials_give_up_when_resource_user_times_out() {
+    let tests = ["db_1", "db_2"].iter()
+        .map(|name| Trial::test(*name, || {
+            thread::sleep(Duration::from_secs(60));
+            Ok(())
+        }).with_resource("db"))
+        .collect();
+
+    let (c, out) = do_run(args(["--test-threads=2", "--test-timeout=0.2"]), tests);
+    assert_eq!(c, Conclusion { num_failed: 2, ..Default::default() });
+    assert_eq!(out.matches("test timed out after 0.20s").count(), 1, "{}", out);
+    assert_eq!(out.matches("timed out waiting for resource `db`").count(), 1, "{}", out);
+}
@@ -0,0 +1,113 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::{args, check};
+use libtest_mimic::{Trial, Conclusion};
+
+#[macro_use]
+mod common;
+
+
+/// A repeatable test that fails the first `failures` times it runs.
+fn flaky(name: &'static str, failures: usize) -> Trial {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    Trial::repeatable_test(name, move || {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        if attempt <= failures {
+            Err(format!("attempt {} failed", attempt).into())
+        } else {
+            Ok(())
+        }
+    })
+}
+
+fn tests() -> Vec<Trial> {
+    vec![
+        flaky("stable", 0),
+        flaky("flaky", 1),
+        flaky("very_flaky", 2),
+        flaky("broken", 5),
+        Trial::test("once", || Err("not repeatable".into())),
+        Trial::test("ignored", || Err("never runs".into())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn retries() {
+    check(args(["--retries=2", "--test-threads=2"]), tests, 6,
+        Conclusion {
+            num_passed: 3,
+            num_failed: 2,
+            num_ignored: 1,
+            ..Default::default()
+        },
+        "
+            test stable     ... ok
+            test flaky      ... ok (after 1 retry)
+            test very_flaky ... ok (after 2 retries)
+            test broken     ... FAILED (after 2 retries)
+            test once       ... FAILED
+            test ignored    ... ignored
+
+            failures:
+
+            ---- broken stdout ----
+            attempt 3 failed
+
+            ---- once stdout ----
+            not repeatable
+
+
+            failures:
+                broken
+                once
+
+            to rerun: <exe> --exact broken once
+        ",
+    );
+}
+
+#[test]
+fn no_retries_by_default() {
+    check(args([]), tests, 6,
+        Conclusion {
+            num_passed: 1,
+            num_failed: 4,
+            num_ignored: 1,
+            ..Default::default()
+        },
+        "
+            test stable     ... ok
+            test flaky      ... FAILED
+            test very_flaky ... FAILED
+            test broken     ... FAILED
+            test once       ... FAILED
+            test ignored    ... ignored
+
+            failures:
+
+            ---- flaky stdout ----
+            attempt 1 failed
+
+            ---- very_flaky stdout ----
+            attempt 1 failed
+
+            ---- broken stdout ----
+            attempt 1 failed
+
+            ---- once stdout ----
+            not repeatable
+
+
+            failures:
+                flaky
+                very_flaky
+                broken
+                once
+
+            to rerun: <exe> --exact flaky very_flaky broken once
+        ",
+    );
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use common::args;
+use libtest_mimic::{run, Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || {
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(())
+        }),
+    ]
+}
+
+#[test]
+fn run_info() {
+    let info = run(&args(["--test-threads", "1", "--logfile", "/dev/null"]), tests()).run_info;
+    assert_eq!(info.num_threads, 1);
+    assert_eq!(info.shuffle_seed, None);
+    assert!(info.elapsed >= Duration::from_millis(20));
+
+    let a = args(["--test-threads", "3", "--shuffle-seed", "7", "--logfile", "/dev/null"]);
+    let info = run(&a, tests()).run_info;
+    assert_eq!(info.num_threads, 3);
+    assert_eq!(info.shuffle_seed, Some(7));
+}
+
+#[test]
+fn list_has_no_run_info() {
+    let info = run(&args(["--list", "--logfile", "/dev/null"]), tests()).run_info;
+    assert_eq!(info, Default::default());
+}
+
+#[test]
+fn equality_ignores_run_info() {
+    let c = run(&args(["--test-threads", "3", "--logfile", "/dev/null"]), tests());
+    assert_eq!(c, Conclusion { num_passed: 2, ..Default::default() });
+}
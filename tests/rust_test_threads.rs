@@ -0,0 +1,32 @@
+use common::args;
+use libtest_mimic::{run, Trial};
+use pretty_assertions::assert_eq;
+
+mod common;
+
+
+fn num_threads<const N: usize>(flags: [&str; N], env: Option<&str>) -> usize {
+    match env {
+        Some(value) => std::env::set_var("RUST_TEST_THREADS", value),
+        None => std::env::remove_var("RUST_TEST_THREADS"),
+    }
+    let mut args = args(flags);
+    args.logfile = Some("/dev/null".into());
+    run(&args, vec![Trial::test("cat", || Ok(()))]).run_info.num_threads
+}
+
+// This is the only test in this binary, as it changes the environment.
+#[test]
+fn rust_test_threads() {
+    let num_cpus = num_threads([], None);
+    assert_eq!(num_threads([], Some("3")), 3);
+    assert_eq!(num_threads([], Some("1")), 1);
+
+    // Invalid values are ignored.
+    assert_eq!(num_threads([], Some("many")), num_cpus);
+    assert_eq!(num_threads([], Some("0")), num_cpus);
+
+    // `--test-threads` takes precedence.
+    assert_eq!(num_threads(["--test-threads=2"], Some("3")), 2);
+    assert_eq!(num_threads(["--test-threads=0"], Some("3")), num_cpus);
+}
@@ -0,0 +1,60 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Barrier,
+};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+/// Two trials of kind `a`, which only pass if they run at the same time, and
+/// two of kind `b`, which check that both `a` trials finished.
+fn tests() -> Vec<Trial> {
+    let barrier = Arc::new(Barrier::new(2));
+    let finished_a = Arc::new(AtomicUsize::new(0));
+    let a = |name: &str| {
+        let (barrier, finished_a) = (barrier.clone(), finished_a.clone());
+        Trial::test(name, move || {
+            barrier.wait();
+            finished_a.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }).with_kind("a")
+    };
+    let b = |name: &str| {
+        let finished_a = finished_a.clone();
+        Trial::test(name, move || {
+            assert_eq!(finished_a.load(Ordering::SeqCst), 2);
+            Ok(())
+        }).with_kind("b")
+    };
+    vec![a("a1"), b("b1"), Trial::test("ignored", || Ok(())).with_kind("c").ignored(), a("a2"),
+        b("b2")]
+}
+
+#[test]
+fn kinds_run_one_after_another() {
+    let (c, out) = do_run(args(["--serial-kinds", "--test-threads=4"]), tests());
+    assert_eq!((c.num_passed, c.num_failed, c.num_ignored), (4, 0, 1), "{}", out);
+}
+
+#[test]
+fn trials_are_grouped_by_kind() {
+    let (c, out) = do_run(args(["--serial-kinds", "--test-threads=1"]), vec![
+        Trial::test("b1", || Ok(())).with_kind("b"),
+        Trial::test("a", || Ok(())).with_kind("a"),
+        Trial::test("b2", || Ok(())).with_kind("b"),
+    ]);
+    assert_eq!(c.num_passed, 3);
+    assert_log!(out, "
+        running 3 tests
+        test [b] b1 ... ok
+        test [b] b2 ... ok
+        test [a] a  ... ok
+
+        test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
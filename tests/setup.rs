@@ -0,0 +1,66 @@
+use std::sync::{Arc, Mutex};
+
+use common::args;
+use libtest_mimic::{run_with_setup, Arguments, Conclusion, Failed, Trial};
+
+#[macro_use]
+mod common;
+
+
+type Events = Arc<Mutex<Vec<&'static str>>>;
+
+fn trial(events: &Events, name: &'static str, result: Result<(), &'static str>) -> Trial {
+    let events = events.clone();
+    Trial::test(name, move || {
+        events.lock().unwrap().push(name);
+        match result {
+            Ok(()) => Ok(()),
+            Err("panic") => panic!("oh no"),
+            Err(msg) => Err(Failed::from(msg)),
+        }
+    })
+}
+
+fn run(args: Arguments) -> (Conclusion, Vec<&'static str>) {
+    let events = Events::default();
+    let tests = vec![
+        trial(&events, "passes", Ok(())),
+        trial(&events, "panics", Err("panic")),
+        trial(&events, "fails", Err("nope")),
+    ];
+
+    let (before, after) = (events.clone(), events.clone());
+    let c = run_with_setup(
+        &args,
+        tests,
+        move || before.lock().unwrap().push("before_all"),
+        move || after.lock().unwrap().push("after_all"),
+    );
+
+    let events = events.lock().unwrap().clone();
+    (c, events)
+}
+
+#[test]
+fn called_once_around_the_run() {
+    for threads in ["1", "2"] {
+        let (c, events) = run(args(["--test-threads", threads, "--logfile", "/dev/null"]));
+        assert_eq!(c.num_failed, 2);
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], "before_all");
+        assert_eq!(events[4], "after_all");
+    }
+}
+
+#[test]
+fn called_if_everything_is_filtered_out() {
+    let (c, events) = run(args(["--logfile", "/dev/null", "--exact", "nothing"]));
+    assert_eq!(c.num_filtered_out, 3);
+    assert_eq!(events, ["before_all", "after_all"]);
+}
+
+#[test]
+fn not_called_for_list() {
+    let (_, events) = run(args(["--logfile", "/dev/null", "--list"]));
+    assert!(events.is_empty());
+}
@@ -0,0 +1,67 @@
+use common::{args, check};
+use libtest_mimic::{Trial, Conclusion};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || panic!("fell off the roof")).with_should_panic(None),
+        Trial::test("dog", || Ok(())).with_should_panic(None),
+        Trial::test("fox", || panic!("jumped over the dog"))
+            .with_should_panic(Some("over the")),
+        Trial::test("bunny", || panic!("hopped away"))
+            .with_should_panic(Some("over the")),
+        Trial::test("frog", || Ok(())),
+    ]
+}
+
+#[test]
+fn should_panic() {
+    check(args([]), tests, 5,
+        Conclusion {
+            num_passed: 3,
+            num_failed: 2,
+            ..Default::default()
+        },
+        r#"
+            test cat   ... ok
+            test dog   ... FAILED
+            test fox   ... ok
+            test bunny ... FAILED
+            test frog  ... ok
+
+            failures:
+
+            ---- dog stdout ----
+            test did not panic as expected
+
+            ---- bunny stdout ----
+            panic did not contain expected string
+                  panic message: "hopped away",
+             expected substring: "over the"
+
+
+            failures:
+                dog
+                bunny
+
+            to rerun: <exe> --exact dog bunny
+        "#,
+    );
+}
+
+#[test]
+fn exclude_should_panic() {
+    check(args(["--exclude-should-panic"]), tests, 1,
+        Conclusion {
+            num_filtered_out: 4,
+            num_passed: 1,
+            ..Default::default()
+        },
+        "
+            test frog ... ok
+        ",
+    );
+}
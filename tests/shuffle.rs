@@ -0,0 +1,58 @@
+use std::sync::{Arc, Mutex};
+
+use common::{args, do_run};
+use libtest_mimic::{Trial, Arguments};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+/// Runs ten tests in the main thread and returns the order they ran in and
+/// the output.
+fn run_order(args: Arguments) -> (Vec<String>, String) {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let tests = (0..10).map(|i| {
+        let order = order.clone();
+        Trial::test(format!("t{i}"), move || {
+            order.lock().unwrap().push(format!("t{i}"));
+            Ok(())
+        })
+    }).collect();
+
+    let mut args = args;
+    args.test_threads = Some(1);
+    let (c, out) = do_run(args, tests);
+    assert_eq!(c.num_passed + c.num_filtered_out, 10);
+
+    let order = order.lock().unwrap().clone();
+    (order, out)
+}
+
+#[test]
+fn same_seed_same_order() {
+    let (a, out) = run_order(args(["--shuffle-seed=42"]));
+    let (b, _) = run_order(args(["--shuffle", "--shuffle-seed=42"]));
+    assert_eq!(a, b);
+    assert_ne!(a, (0..10).map(|i| format!("t{i}")).collect::<Vec<_>>());
+    assert!(!out.contains("shuffle seed"));
+}
+
+#[test]
+fn random_seed_is_printed() {
+    let (a, out) = run_order(args(["--shuffle"]));
+    let first_line = out.lines().next().unwrap();
+    let seed = first_line.strip_prefix("-- using shuffle seed ")
+        .expect("seed not printed");
+
+    let (b, _) = run_order(args(["--shuffle-seed", seed]));
+    assert_eq!(a, b);
+}
+
+#[test]
+fn shuffle_after_filtering() {
+    let (order, out) = run_order(args(["--shuffle-seed=7", "--skip=t1"]));
+    assert_eq!(order.len(), 9);
+    assert!(out.contains("running 9 tests"));
+    assert!(out.contains("9 passed; 0 failed; 0 ignored; 0 measured; 1 filtered out"));
+}
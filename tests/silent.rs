@@ -0,0 +1,45 @@
+use common::{args, do_run};
+use libtest_mimic::{Failed, Trial};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Err(Failed::from("was not a good boy"))),
+        Trial::test("fox", || Ok(())).with_ignored_flag(true),
+        Trial::bench("red", |_| Ok(None)),
+    ]
+}
+
+#[test]
+fn only_failures_and_summary() {
+    let (c, out) = do_run(args(["--silent"]), tests());
+    assert_eq!(c.num_failed, 1);
+    assert_log!(out, "
+        failures:
+
+        ---- dog stdout ----
+        was not a good boy
+
+
+        failures:
+            dog
+
+        to rerun: <exe> --exact dog
+
+        test result: FAILED. 2 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn all_passing() {
+    let (_, out) = do_run(args(["-Q", "--skip", "dog"]), tests());
+    assert_log!(out, "
+        test result: ok. 2 passed; 0 failed; 1 ignored; 0 measured; 1 filtered out; \
+            finished in 0.00s
+    ");
+}
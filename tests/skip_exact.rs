@@ -0,0 +1,36 @@
+use common::run_names;
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("parse", || Ok(())),
+        Trial::test("parse_json", || Ok(())),
+        Trial::test("parse_toml", || Ok(())),
+        Trial::test("print", || Ok(())),
+    ]
+}
+
+#[test]
+fn skip_exact() {
+    // Without `--skip-exact`, `--skip` matches substrings.
+    assert_eq!(run_names(["--skip", "parse", "pars"], tests()), Vec::<String>::new());
+
+    // The filter still matches substrings, only `--skip` is exact.
+    assert_eq!(
+        run_names(["--skip-exact", "--skip", "parse", "pars"], tests()),
+        ["parse_json", "parse_toml"],
+    );
+    assert_eq!(
+        run_names(["--skip-exact", "--skip", "pars", "pars"], tests()),
+        ["parse", "parse_json", "parse_toml"],
+    );
+
+    // `--exact` still applies to both.
+    let names = run_names(["--exact", "--skip-exact", "--skip", "parse", "parse"], tests());
+    assert_eq!(names.len(), 0);
+    assert_eq!(run_names(["--exact", "--skip", "parse", "print"], tests()), ["print"]);
+}
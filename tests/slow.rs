@@ -0,0 +1,42 @@
+use std::{thread, time::Duration};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("fast", || Ok(())),
+        Trial::test("sleepy", || {
+            thread::sleep(Duration::from_millis(300));
+            Ok(())
+        }),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn slow_tests_are_marked() {
+    let (c, out) = do_run(args(["--slow-threshold=0.1"]), tests());
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(c.num_slow, 1);
+
+    let lines = out.lines().filter(|l| l.starts_with("test ")).collect::<Vec<_>>();
+    assert_eq!(lines[0], "test fast    ... ok");
+    assert!(lines[1].starts_with("test sleepy  ... ok <0."), "{}", lines[1]);
+    assert!(lines[1].ends_with("s> (slow)"), "{}", lines[1]);
+    assert_eq!(lines[2], "test ignored ... ignored");
+    assert!(out.contains("; 1 slow; finished in"), "{}", out);
+}
+
+#[test]
+fn no_op_without_threshold() {
+    let (c, out) = do_run(args([]), tests());
+    assert_eq!(c.num_slow, 0);
+    assert!(!out.contains("slow"));
+    assert!(out.contains("test sleepy  ... ok\n"));
+}
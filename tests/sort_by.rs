@@ -0,0 +1,41 @@
+use common::{args, do_run, run_names};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("fox", || Ok(())).with_kind("b"),
+        Trial::test("cat", || Ok(())),
+        Trial::test("owl", || Ok(())).with_kind("a"),
+        Trial::test("dog", || Ok(())).with_kind("b"),
+    ]
+}
+
+#[test]
+fn sort_by() {
+    assert_eq!(run_names([], tests()), ["fox", "cat", "owl", "dog"]);
+    assert_eq!(run_names(["--sort-by=none"], tests()), ["fox", "cat", "owl", "dog"]);
+    assert_eq!(run_names(["--sort-by=name"], tests()), ["cat", "dog", "fox", "owl"]);
+    assert_eq!(run_names(["--sort-by=kind"], tests()), ["cat", "owl", "dog", "fox"]);
+}
+
+#[test]
+fn partition_uses_sorted_order() {
+    assert_eq!(run_names(["--partition=count:1/2"], tests()), ["fox", "cat"]);
+    assert_eq!(run_names(["--sort-by=name", "--partition=count:1/2"], tests()), ["cat", "dog"]);
+}
+
+#[test]
+fn list_is_sorted() {
+    let (_, out) = do_run(args(["--list", "--sort-by=name"]), tests());
+    assert_log!(out, "
+        cat: test
+        [b] dog: test
+        [b] fox: test
+        [a] owl: test
+    ");
+}
@@ -0,0 +1,74 @@
+use std::{env, process::Command, sync::mpsc, time::Duration};
+
+use common::args;
+use libtest_mimic::{run_streaming, Outcome, Trial};
+use pretty_assertions::assert_eq;
+
+mod common;
+
+
+const CHILD_ENV: &str = "LIBTEST_MIMIC_STREAMING_CHILD";
+
+
+#[test]
+fn results_arrive_while_running() {
+    // `second` only passes if the result of `first` was received before it
+    // started, i.e. results are not held back until the end of the run.
+    let (go_sender, go_receiver) = mpsc::channel::<()>();
+    let tests = vec![
+        Trial::test("first", || Ok(())),
+        Trial::test("second", move || {
+            go_receiver.recv_timeout(Duration::from_secs(10))
+                .map_err(|_| "result of `first` was not streamed".into())
+        }),
+        Trial::test("third", || Err("broken".into())),
+    ];
+
+    let (results, handle) = run_streaming(&args(["--test-threads=1"]), tests);
+    let first = results.recv().unwrap();
+    assert_eq!(first.name, "first");
+    go_sender.send(()).unwrap();
+
+    let rest = results.iter().collect::<Vec<_>>();
+    assert_eq!(rest.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["second", "third"]);
+    assert!(matches!(rest[0].outcome, Outcome::Passed { .. }));
+    assert!(matches!(rest[1].outcome, Outcome::Failed(_)));
+
+    let conclusion = handle.join().unwrap();
+    assert_eq!(conclusion.num_passed, 2);
+    assert_eq!(conclusion.num_failed, 1);
+}
+
+#[test]
+fn receiver_dropped() {
+    let tests = vec![Trial::test("cat", || Ok(())), Trial::test("dog", || Ok(()))];
+    let (results, handle) = run_streaming(&args([]), tests);
+    drop(results);
+    assert_eq!(handle.join().unwrap().num_passed, 2);
+}
+
+/// Runs in a child process (see `prints_nothing`), as warnings would be
+/// written to stderr.
+#[test]
+#[ignore]
+fn child() {
+    env::var(CHILD_ENV).expect("only run by `prints_nothing`");
+    for format in ["json", "junit"] {
+        let tests = vec![Trial::test("cat", || Ok(()))];
+        let flags = ["--format", format, "--shuffle", "dog"];
+        let (results, handle) = run_streaming(&args(flags), tests);
+        drop(results);
+        assert_eq!(handle.join().unwrap().exit_code(), 2);
+    }
+}
+
+#[test]
+fn prints_nothing() {
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "child", "--ignored", "--nocapture"])
+        .env(CHILD_ENV, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+}
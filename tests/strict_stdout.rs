@@ -0,0 +1,42 @@
+//! This file intentionally contains a single test: redirecting stdout affects
+//! the whole process, including other tests running in parallel.
+#![cfg(unix)]
+
+use std::io::Write;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+#[test]
+fn warns_about_stdout_writes() {
+    let tests = || vec![
+        Trial::test("quiet", || Ok(())),
+        Trial::test("noisy", || {
+            // `println!` would be captured by the test harness running this
+            // file, so we write to stdout directly.
+            writeln!(std::io::stdout(), "hello from noisy").unwrap();
+            Ok(())
+        }),
+    ];
+
+    let mut args = args(["--strict-stdout"]);
+    args.test_threads = Some(1);
+    let (c, out) = do_run(args.clone(), tests());
+    assert_eq!(c.num_passed, 2);
+    let lines = out.trim().lines().skip(1).take(4).collect::<Vec<_>>().join("\n");
+    assert_log!(lines, "
+        test quiet ... ok
+        test noisy ... ok
+        warning: test noisy wrote to stdout without --nocapture
+        hello from noisy
+    ");
+
+    // No warning without the flag or with `--nocapture`.
+    args.nocapture = true;
+    let (_, out) = do_run(args, tests());
+    assert!(!out.contains("warning"));
+}
@@ -0,0 +1,56 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())).with_kind("unit"),
+        Trial::test("dog", || Err("was not a good boy".into())).with_kind("integration"),
+        Trial::test("fox", || Ok(())).with_kind("integration"),
+        Trial::test("bunny", || Ok(())).with_kind("unit").with_ignored_flag(true),
+        Trial::test("frog", || Ok(())),
+    ]
+}
+
+#[test]
+fn summary_by_kind() {
+    let args = args(["--summary-by-kind", "--format", "terse", "--test-threads", "1"]);
+    let (_, out) = do_run(args, tests());
+    assert_log!(out, "
+        running 5 tests
+        .F.i.
+        failures:
+
+        ---- dog stdout ----
+        was not a good boy
+
+
+        failures:
+            dog
+
+        to rerun: <exe> --exact dog
+
+        test result: FAILED. 3 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+
+        summary by kind:
+            (no kind)     1 passed; 0 failed; 0 ignored; 0 measured
+            [integration] 1 passed; 1 failed; 0 ignored; 0 measured
+            [unit]        1 passed; 0 failed; 1 ignored; 0 measured
+    ");
+}
+
+#[test]
+fn single_kind_has_no_breakdown() {
+    let (_, out) = do_run(args(["--summary-by-kind", "--skip", "o"]), tests());
+    assert!(!out.contains("summary by kind"));
+}
+
+#[test]
+fn off_by_default() {
+    let (_, out) = do_run(args([]), tests());
+    assert!(!out.contains("summary by kind"));
+}
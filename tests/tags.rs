@@ -0,0 +1,34 @@
+use common::run_names;
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Ok(())).with_tag("slow"),
+        Trial::test("fox", || Ok(())).with_tag("network"),
+        Trial::test("owl", || Ok(())).with_tag("slow").with_tag("network"),
+    ]
+}
+
+#[test]
+fn tags() {
+    assert_eq!(tests()[3].tags(), ["slow", "network"]);
+
+    assert_eq!(run_names(["--tag", "slow"], tests()), ["dog", "owl"]);
+    assert_eq!(run_names(["--tag", "slow", "--tag", "network"], tests()), ["dog", "fox", "owl"]);
+    assert_eq!(run_names(["--tag", "slow", "--tag", "network", "--all-tags"], tests()), ["owl"]);
+    assert_eq!(run_names(["--tag", "gpu"], tests()), Vec::<String>::new());
+}
+
+#[test]
+fn exclude_tags() {
+    assert_eq!(run_names(["--exclude-tag", "slow"], tests()), ["cat", "fox"]);
+    assert_eq!(run_names(["--exclude-tag", "slow", "--exclude-tag", "network"], tests()), ["cat"]);
+
+    // Excluding takes precedence.
+    assert_eq!(run_names(["--tag", "network", "--exclude-tag", "slow"], tests()), ["fox"]);
+}
@@ -0,0 +1,51 @@
+use common::{args, do_run};
+use libtest_mimic::{Trial, Measurement};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Err("was not a good boy\nat all".into())),
+        Trial::test("frog", || Ok(())).with_ignored_flag(true),
+        Trial::test("issue #7", || Ok(())),
+        Trial::test("silent", || Err(libtest_mimic::Failed::without_message())),
+    ]
+}
+
+#[test]
+fn tap_output() {
+    let (c, out) = do_run(args(["--format=tap", "--test-threads=1"]), tests());
+    assert_eq!(c.num_failed, 2);
+    assert_log!(out, r"
+        TAP version 13
+        1..5
+        ok 1 - cat
+        not ok 2 - dog
+          ---
+          message: |
+            was not a good boy
+            at all
+          ...
+        ok 3 - frog # SKIP
+        ok 4 - issue \#7
+        not ok 5 - silent
+        # 2 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out
+    ");
+}
+
+#[test]
+fn tap_bench() {
+    let tests = vec![
+        Trial::bench("fast", |_| Ok(Some(Measurement { avg: 1500, variance: 20 }))),
+    ];
+    let (_, out) = do_run(args(["--format=tap", "--bench"]), tests);
+    assert_log!(out, "
+        TAP version 13
+        1..1
+        ok 1 - fast # 1,500 ns/iter (+/- 20)
+        # 0 passed; 0 failed; 0 ignored; 1 measured; 0 filtered out
+    ");
+}
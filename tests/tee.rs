@@ -0,0 +1,55 @@
+use std::{env, fs, path::Path, process::Command};
+
+use common::TEMPDIR;
+use libtest_mimic::{run, Arguments, Trial};
+
+#[macro_use]
+mod common;
+
+
+const CHILD_ENV: &str = "LIBTEST_MIMIC_TEE_CHILD";
+
+/// Runs in a child process (see `writes_to_stdout_and_logfile`), as `--tee`
+/// writes to stdout. The logfile path is passed in the environment variable.
+#[test]
+#[ignore]
+fn child() {
+    let logfile = env::var(CHILD_ENV).expect("only run by `writes_to_stdout_and_logfile`");
+    let tests = vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Err("was not a good boy\nat all".into())),
+    ];
+    let args = Arguments::from_iter([
+        "<child>", "--test-threads", "1", "--color", "always", "--tee", "--logfile", &logfile,
+        "--max-message-lines", "1",
+    ]);
+    let _ = run(&args, tests);
+}
+
+#[test]
+fn writes_to_stdout_and_logfile() {
+    let logfile = Path::new(TEMPDIR).join("libtest_mimic_tee.txt");
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "child", "--ignored", "--nocapture"])
+        .env(CHILD_ENV, &logfile)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let log = fs::read_to_string(&logfile).unwrap();
+    fs::remove_file(&logfile).unwrap();
+
+    // Stdout is colored, the logfile is not.
+    assert!(stdout.contains("test cat ... \u{1b}[0m\u{1b}[32mok"), "{}", stdout);
+    assert!(!log.contains('\u{1b}'), "{}", log);
+
+    let start = log.find("running 2 tests").unwrap();
+    assert_eq!(log[start..].trim_end().lines().take(3).collect::<Vec<_>>(), [
+        "running 2 tests",
+        "test cat ... ok",
+        "test dog ... FAILED",
+    ]);
+    // Only the message on stdout is truncated.
+    assert!(stdout.contains("was not a good boy\n... (truncated, 1 more lines)"), "{}", stdout);
+    assert!(log.contains("was not a good boy\nat all"), "{}", log);
+    assert!(log.contains("test result: FAILED. 1 passed; 1 failed;"));
+}
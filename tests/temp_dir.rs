@@ -0,0 +1,89 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use common::{args, do_run};
+use libtest_mimic::{Trial, TrialResult, Outcome, run_detailed};
+
+#[macro_use]
+mod common;
+
+
+type Dirs = Arc<Mutex<Vec<(String, PathBuf)>>>;
+
+fn tests(dirs: &Dirs) -> Vec<Trial> {
+    let trial = |name: &'static str, fail: bool| {
+        let dirs = dirs.clone();
+        Trial::test_in_temp_dir(name, move |dir| {
+            assert!(dir.is_dir());
+            assert_eq!(dir.read_dir().unwrap().count(), 0);
+            std::fs::write(dir.join("scratch.txt"), name).unwrap();
+            dirs.lock().unwrap().push((name.to_owned(), dir.to_owned()));
+            if fail {
+                Err("nope".into())
+            } else {
+                Ok(())
+            }
+        })
+    };
+
+    vec![trial("a", false), trial("b", false), trial("fails", true)]
+}
+
+#[test]
+fn distinct_dirs_removed_after_test() {
+    let dirs = Dirs::default();
+    let (c, _) = do_run(args([]), tests(&dirs));
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(c.num_failed, 1);
+
+    let dirs = dirs.lock().unwrap();
+    assert_eq!(dirs.len(), 3);
+    assert_ne!(dirs[0].1, dirs[1].1);
+    assert_ne!(dirs[1].1, dirs[2].1);
+    assert_ne!(dirs[0].1, dirs[2].1);
+    for (_, dir) in dirs.iter() {
+        assert!(!dir.exists());
+    }
+}
+
+#[test]
+fn kept_on_failure() {
+    let dirs = Dirs::default();
+    let args = args(["--keep-temp-on-failure", "--logfile", "/dev/null"]);
+    let (_, results) = run_detailed(&args, tests(&dirs));
+
+    let dirs = dirs.lock().unwrap();
+    for (name, dir) in dirs.iter() {
+        assert_eq!(dir.exists(), name == "fails");
+    }
+
+    let (_, kept) = dirs.iter().find(|(name, _)| name == "fails").unwrap();
+    let msg = results.iter()
+        .find_map(|r| match r {
+            TrialResult { outcome: Outcome::Failed(f), .. } => f.message().map(String::from),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(msg, format!("nope\ntemporary directory kept at {}", kept.display()));
+    assert_eq!(std::fs::read_to_string(kept.join("scratch.txt")).unwrap(), "fails");
+    std::fs::remove_dir_all(kept).unwrap();
+}
+
+#[test]
+fn kept_on_panic() {
+    let dir = Arc::new(Mutex::new(None));
+    let dir2 = dir.clone();
+    let tests = vec![Trial::test_in_temp_dir("panics", move |d| {
+        *dir2.lock().unwrap() = Some(d.to_owned());
+        panic!("oh no");
+    })];
+
+    let (c, out) = do_run(args(["--keep-temp-on-failure"]), tests);
+    assert_eq!(c.num_failed, 1);
+    let dir = dir.lock().unwrap().clone().unwrap();
+    assert!(dir.is_dir());
+    assert!(out.contains("test panicked: oh no\ntemporary directory kept at "));
+    std::fs::remove_dir(dir).unwrap();
+}
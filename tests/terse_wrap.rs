@@ -0,0 +1,67 @@
+use std::{env, process::{Command, Stdio}};
+
+use common::clean_expected_log;
+use libtest_mimic::{run, Arguments, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+const CHILD_ENV: &str = "LIBTEST_MIMIC_TERSE_WRAP_CHILD";
+
+/// Runs in a child process (see `child_output`), as only output to a
+/// terminal is wrapped.
+#[test]
+#[ignore]
+fn child() {
+    env::var(CHILD_ENV).expect("only run by `child_output`");
+    let tests = (0..30).map(|i| Trial::test(format!("t{i}"), || Ok(()))).collect();
+    let args = Arguments::from_iter(
+        ["<child>", "--format", "terse", "--test-threads", "1", "--color", "never"],
+    );
+    let _ = run(&args, tests);
+}
+
+/// Runs `child` with `COLUMNS=20` and returns its output up to the summary.
+/// With `in_terminal`, the child runs in a pseudo terminal via `script`.
+fn child_output(in_terminal: bool) -> String {
+    let exe = env::current_exe().unwrap();
+    let args = ["--exact", "child", "--ignored", "--nocapture"];
+    let mut command = if in_terminal {
+        let mut command = Command::new("script");
+        let child = format!("{} {}", exe.display(), args.join(" "));
+        command.args(["-qec", &child, "/dev/null"]).stdin(Stdio::null());
+        command
+    } else {
+        let mut command = Command::new(exe);
+        command.args(args);
+        command
+    };
+    let output = command.env(CHILD_ENV, "1").env("COLUMNS", "20").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap().replace("\r\n", "\n");
+
+    // Only keep our output, not that of the harness running the child.
+    let start = stdout.find("running 30 tests").unwrap();
+    let end = stdout[start..].find("test result").unwrap();
+    stdout[start..start + end].trim().to_owned()
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn wraps_at_terminal_width() {
+    assert_eq!(child_output(true), clean_expected_log("
+        running 30 tests
+        .............. 14/30
+        .............. 28/30
+        ..
+    ").trim());
+}
+
+#[test]
+fn ignores_columns_if_not_a_terminal() {
+    assert_eq!(child_output(false), clean_expected_log("
+        running 30 tests
+        ..............................
+    ").trim());
+}
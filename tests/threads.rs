@@ -0,0 +1,62 @@
+use std::thread;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    (0..20).map(|i| Trial::test(format!("t{i}"), || Ok(()))).collect()
+}
+
+#[test]
+fn zero_uses_all_cpus() {
+    let (c, _) = do_run(args(["--test-threads=0"]), tests());
+    assert_eq!(c.num_passed, 20);
+}
+
+#[test]
+fn more_than_one_thread_runs_off_main_thread() {
+    let outer_thread = thread::current().id();
+    let tests = vec![Trial::test("check", move || {
+        assert_ne!(outer_thread, thread::current().id());
+        Ok(())
+    })];
+
+    let (c, _) = do_run(args(["--test-threads=2"]), tests);
+    assert_eq!(c.num_passed, 1);
+}
+
+// On macOS, the OS-level name cannot be read back easily.
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn threads_are_named_after_trials() {
+    // Linux truncates thread names to 15 bytes.
+    let names = [("t0", "t0"), ("t1", "t1"), ("a_rather_long_trial_name", "a_rather_long_t")];
+    let tests = names.iter()
+        .map(|&(name, truncated)| {
+            let expected = if cfg!(target_os = "linux") { truncated } else { name };
+            Trial::test(name, move || {
+                assert_eq!(current_thread_name(), expected);
+                Ok(())
+            })
+        })
+        .collect();
+
+    let (c, _) = do_run(args(["--test-threads=2"]), tests);
+    assert_eq!(c.num_passed, 3);
+}
+
+/// Returns the name of the current thread as profilers see it.
+#[cfg(target_os = "linux")]
+fn current_thread_name() -> String {
+    let name = std::fs::read_to_string("/proc/thread-self/comm").unwrap();
+    name.trim_end_matches('\n').to_owned()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn current_thread_name() -> String {
+    thread::current().name().unwrap().to_owned()
+}
@@ -0,0 +1,63 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use common::{args, do_run, TEMPDIR};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+const RECORDED: &[(&str, f64)] = &[("a", 0.5), ("b", 0.1), ("c", 0.3), ("d", 2.0)];
+
+fn tests(order: &Arc<Mutex<Vec<&'static str>>>) -> Vec<Trial> {
+    ["a", "b", "c", "d", "new"].into_iter()
+        .map(|name| {
+            let order = order.clone();
+            Trial::test(name, move || {
+                order.lock().unwrap().push(name);
+                Ok(())
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn fastest_tests_within_budget() {
+    let path = Path::new(TEMPDIR).join("libtest_mimic_timings.txt");
+    let recorded = RECORDED.iter()
+        .map(|(name, secs)| format!("{} {}\n", secs, name))
+        .collect::<String>();
+    fs::write(&path, recorded).unwrap();
+
+    let mut args = args(["--timings", path.to_str().unwrap(), "--time-budget=0.5"]);
+    args.test_threads = Some(1);
+    let order = Arc::default();
+    let (c, _) = do_run(args, tests(&order));
+
+    // Tests without recorded duration are always run.
+    let order = order.lock().unwrap().clone();
+    assert_eq!(order, ["b", "c", "new"]);
+    assert_eq!(c.num_filtered_out, 2);
+    let estimated = RECORDED.iter()
+        .filter(|(name, _)| order.contains(name))
+        .map(|(_, secs)| secs)
+        .sum::<f64>();
+    assert!(estimated <= 0.5);
+
+    // The durations of tests that ran are updated, the others are kept.
+    let timings = fs::read_to_string(&path).unwrap();
+    let names = timings.lines()
+        .map(|line| line.split_once(' ').unwrap())
+        .map(|(secs, name)| {
+            assert!(secs.parse::<f64>().is_ok());
+            name
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["a", "b", "c", "d", "new"]);
+    assert!(timings.starts_with("0.5 a\n"));
+    assert!(timings.contains("\n2 d\n"));
+}
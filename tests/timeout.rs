@@ -0,0 +1,71 @@
+use std::{thread, time::Duration};
+
+use common::{args, assert_reordered_log, do_run};
+use libtest_mimic::{Trial, Conclusion};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("fast", || Ok(())),
+        Trial::test("hangs", || {
+            thread::sleep(Duration::from_secs(60));
+            Ok(())
+        }),
+        Trial::test("fails", || Err("nope".into())),
+    ]
+}
+
+#[test]
+fn parse() {
+    assert_eq!(args(["--test-timeout=2"]).timeout, Some(Duration::from_secs(2)));
+    assert_eq!(args(["--test-timeout", "0.5"]).timeout, Some(Duration::from_millis(500)));
+    assert_eq!(args([]).timeout, None);
+}
+
+#[test]
+fn hanging_test_times_out() {
+    // Timeouts are only enforced when running tests in parallel.
+    let (c, out) = do_run(args(["--test-timeout=0.2", "--test-threads=2"]), tests());
+
+    assert_eq!(c, Conclusion {
+        num_passed: 1,
+        num_failed: 2,
+        ..Default::default()
+    });
+    assert_reordered_log(&out, 3, &[
+        "test fast  ... ok",
+        "test hangs ... FAILED",
+        "test fails ... FAILED",
+        "failures:",
+        "---- hangs stdout ----",
+        "test timed out after 0.20s",
+        "---- fails stdout ----",
+        "nope",
+        "failures:",
+        "hangs",
+        "fails",
+        // `fails` always finishes first, as `hangs` runs into the timeout.
+        "to rerun: <exe> --exact fails hangs",
+    ], "test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out;");
+}
+
+#[test]
+fn timeout_does_not_starve_queued_tests() {
+    let hanging = (0..4).map(|i| {
+        Trial::test(format!("hangs{i}"), || {
+            thread::sleep(Duration::from_secs(60));
+            Ok(())
+        })
+    });
+    let passing = (0..4).map(|i| Trial::test(format!("passes{i}"), || Ok(())));
+
+    let mut args = args(["--test-timeout=0.1"]);
+    args.test_threads = Some(2);
+    let (c, _) = do_run(args, hanging.chain(passing).collect());
+    assert_eq!(c.num_passed, 4);
+    assert_eq!(c.num_failed, 4);
+}
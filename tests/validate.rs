@@ -0,0 +1,91 @@
+use std::{env, process::Command};
+
+use common::args;
+use libtest_mimic::{run, Arguments, Trial};
+use pretty_assertions::assert_eq;
+
+mod common;
+
+
+const CHILD_ENV: &str = "LIBTEST_MIMIC_VALIDATE_CHILD";
+
+#[test]
+fn valid() {
+    assert_eq!(Arguments::default().validate(), Ok(()));
+    assert_eq!(args(["--ignored", "--shuffle-seed=3", "--exact", "--prefix"]).validate(), Ok(()));
+    assert_eq!(args(["--list", "--names-only"]).validate(), Ok(()));
+}
+
+#[test]
+fn conflicts() {
+    let mut a = args(["--include-ignored"]);
+    a.ignored = true;
+    assert_eq!(a.validate(), Err("--ignored cannot be used with --include-ignored".into()));
+
+    let mut a = args(["--test"]);
+    a.bench = true;
+    assert_eq!(a.validate(), Err("--test cannot be used with --bench".into()));
+
+    let mut a = args(["-q"]);
+    a.format = Some(libtest_mimic::FormatSetting::Json);
+    assert_eq!(a.validate(), Err("--quiet cannot be used with --format".into()));
+
+    let mut a = args(["--silent"]);
+    a.quiet = true;
+    assert_eq!(a.validate(), Err("--silent cannot be used with --quiet".into()));
+}
+
+#[test]
+fn requirements() {
+    let a = Arguments { names_only: true, ..Arguments::default() };
+    assert_eq!(a.validate(), Err("--names-only requires --list".into()));
+
+    let a = Arguments { failed_first: true, ..Arguments::default() };
+    assert_eq!(a.validate(), Err("--failed-first requires --history".into()));
+
+    let a = Arguments { tee: true, ..Arguments::default() };
+    assert_eq!(a.validate(), Err("--tee requires --logfile".into()));
+}
+
+/// Runs in a child process (see `run_exits_on_invalid_arguments`) and passes
+/// the invalid arguments named by `CHILD_ENV` to `run`.
+#[test]
+#[ignore]
+fn child() {
+    let case = env::var(CHILD_ENV).expect("only run by `run_exits_on_invalid_arguments`");
+    let mut args = Arguments::default();
+    match &*case {
+        "conflict" => {
+            args.ignored = true;
+            args.include_ignored = true;
+        }
+        "regex" => {
+            args.filter_regex = true;
+            args.filters = vec!["parse_(".into()];
+        }
+        _ => panic!("unknown case {}", case),
+    }
+    let _ = run(&args, vec![Trial::test("cat", || Ok(()))]);
+}
+
+fn run_child(case: &str) -> (Option<i32>, String) {
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "child", "--ignored", "--nocapture"])
+        .env(CHILD_ENV, case)
+        .output()
+        .unwrap();
+    (output.status.code(), String::from_utf8(output.stderr).unwrap())
+}
+
+#[test]
+fn run_exits_on_invalid_arguments() {
+    let (code, stderr) = run_child("conflict");
+    assert_eq!(code, Some(2), "{}", stderr);
+    assert!(stderr.contains("--ignored cannot be used with --include-ignored"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+
+    let (code, stderr) = run_child("regex");
+    assert_eq!(code, Some(2), "{}", stderr);
+    assert!(stderr.contains("invalid regex in filter"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
@@ -0,0 +1,56 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use common::{args, do_run};
+use libtest_mimic::{run_detailed, Outcome, Trial};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    let runs = AtomicU32::new(0);
+    vec![
+        Trial::repeatable_test("stable", || Ok(())),
+        // Passes the first time, fails the second time.
+        Trial::repeatable_test("flaky", move || {
+            match runs.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok(()),
+                _ => Err("second run failed".into()),
+            }
+        }),
+        Trial::repeatable_test("fails", || Err("always".into())),
+        Trial::test("once", || Ok(())),
+    ]
+}
+
+#[test]
+fn reports_flaky_tests() {
+    for threads in ["1", "2"] {
+        let (c, out) = do_run(args(["--verify-passes", "--test-threads", threads]), tests());
+        assert_eq!(c.num_passed, 2);
+        assert_eq!(c.num_failed, 2);
+        assert!(out.contains(
+            "warning: test flaky is flaky: it passed, but failed when run again (--verify-passes)",
+        ));
+        assert!(out.contains(
+            "---- flaky stdout ----\nflaky, failed when run again: second run failed",
+        ));
+        assert!(out.contains("failures:\n    fails\n    flaky\n"));
+    }
+}
+
+#[test]
+fn results_are_updated() {
+    let args = args(["--verify-passes", "--logfile", "/dev/null"]);
+    let (_, results) = run_detailed(&args, tests());
+    let flaky = results.iter().find(|r| r.name == "flaky").unwrap();
+    assert!(matches!(flaky.outcome, Outcome::Failed(_)));
+}
+
+#[test]
+fn not_verified_without_flag() {
+    let (c, out) = do_run(args([]), tests());
+    assert_eq!(c.num_passed, 3);
+    assert_eq!(c.num_failed, 1);
+    assert!(!out.contains("warning"));
+}
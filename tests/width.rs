@@ -0,0 +1,76 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("plain", || Ok(())),
+        Trial::test("a→b", || Ok(())),
+        Trial::test("cafe\u{301}", || Ok(())),
+    ]
+}
+
+#[test]
+fn strict_width_warns_about_ambiguous_names() {
+    let (_, out) = do_run(args(["--strict-width", "--test-threads=1"]), tests());
+    assert_log!(out, "
+        warning: test name \"a→b\" contains characters of ambiguous or zero width and might be \
+            misaligned
+        warning: test name \"cafe\\u{301}\" contains characters of ambiguous or zero width and \
+            might be misaligned
+
+        running 3 tests
+        test plain ... ok
+        test a→b   ... ok
+        test cafe\u{301}  ... ok
+
+        test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn no_warnings_without_strict_width() {
+    let (_, out) = do_run(args(["--test-threads=1"]), tests());
+    assert!(!out.contains("warning"));
+    assert!(out.contains("test cafe\u{301}  ... ok\n"));
+}
+
+#[test]
+fn wide_names_are_aligned() {
+    let tests = vec![
+        Trial::test("ascii", || Ok(())),
+        Trial::test("日本語", || Ok(())),
+        Trial::test("한국", || Ok(())).with_kind("韓"),
+    ];
+    let (_, out) = do_run(args(["--test-threads=1"]), tests);
+    assert_log!(out, "
+        running 3 tests
+        test      ascii  ... ok
+        test      日本語 ... ok
+        test [韓] 한국   ... ok
+
+        test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn no_align() {
+    let tests = vec![
+        Trial::test("short", || Ok(())),
+        Trial::test("a_pathologically_long_test_name", || Ok(())).with_kind("slow"),
+    ];
+    let (_, out) = do_run(args(["--test-threads=1", "--no-align"]), tests);
+    assert_log!(out, "
+        running 2 tests
+        test short ... ok
+        test [slow] a_pathologically_long_test_name ... ok
+
+        test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
@@ -0,0 +1,89 @@
+use std::{io, thread, time::Duration};
+
+use common::args;
+use libtest_mimic::{run_with_writer, Trial};
+
+#[macro_use]
+mod common;
+
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("nope".into())),
+    ]
+}
+
+#[test]
+fn output_goes_to_writer() {
+    let mut args = args([]);
+    args.test_threads = Some(1);
+
+    let mut out = Vec::new();
+    let c = run_with_writer(&args, tests(), &mut out);
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_failed, 1);
+
+    let out = String::from_utf8(out).unwrap();
+    let lines = out.trim().lines().skip(1).take(2).collect::<Vec<_>>().join("\n");
+    assert_log!(lines, "
+        test passes ... ok
+        test fails  ... FAILED
+    ");
+    assert!(out.contains("test result: FAILED. 1 passed; 1 failed;"));
+    assert!(!out.contains('\u{1b}'));
+}
+
+#[test]
+fn colored_with_color_always() {
+    let mut out = Vec::new();
+    let _ = run_with_writer(&args(["--color=always"]), tests(), &mut out);
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("\u{1b}[0m\u{1b}[32mok\u{1b}[0m"));
+}
+
+/// Records the output written so far at every flush.
+#[derive(Default)]
+struct FlushRecorder {
+    out: Vec<u8>,
+    flushed: Vec<String>,
+}
+
+impl io::Write for FlushRecorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.out.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed.push(String::from_utf8(self.out.clone()).unwrap());
+        Ok(())
+    }
+}
+
+#[test]
+fn flushed_after_each_test() {
+    let mut w = FlushRecorder::default();
+    let _ = run_with_writer(&args(["--test-threads=1"]), tests(), &mut w);
+    assert!(w.flushed.iter().any(|out| out.ends_with("test passes ... ok\n")));
+    assert!(w.flushed.iter().any(|out| out.ends_with("test fails  ... FAILED\n")));
+
+    let mut w = FlushRecorder::default();
+    let _ = run_with_writer(&args(["--test-threads=1", "--format=tap"]), tests(), &mut w);
+    assert!(w.flushed.iter().any(|out| out.ends_with("ok 1 - passes\n")));
+}
+
+#[test]
+fn terse_flushed_periodically() {
+    let slow = |name: &str| Trial::test(name, || {
+        thread::sleep(Duration::from_millis(150));
+        Ok(())
+    });
+    let tests = vec![slow("a"), Trial::test("b", || Ok(())), slow("c")];
+
+    let mut w = FlushRecorder::default();
+    let _ = run_with_writer(&args(["--test-threads=1", "-q"]), tests, &mut w);
+    assert!(w.flushed.iter().any(|out| out.ends_with("running 3 tests\n.")));
+    assert!(!w.flushed.iter().any(|out| out.ends_with("running 3 tests\n..")));
+    assert!(w.flushed.iter().any(|out| out.ends_with("running 3 tests\n...")));
+}